@@ -0,0 +1,270 @@
+use crate::geometry::{self, Rect};
+use crate::label::text_width;
+use crate::{escape, Print, Scale, SelectBox};
+use anyhow::{bail, Result};
+use std::path::Path;
+
+/// A sheet of the atlas
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sheet {
+    /// 1 and up, row by row from the north west
+    pub number: usize,
+    pub select_box: SelectBox,
+    /// The numbers of the sheets to the north, east, south and west
+    pub neighbours: [Option<usize>; 4],
+}
+
+/// Sheets of `w` by `h` metres over the area, `overlap` metres on each
+/// other, centred on the area
+pub fn sheets(area: &SelectBox, w: i64, h: i64, overlap: i64) -> Result<Vec<Sheet>> {
+    if overlap < 0 || w <= overlap || h <= overlap {
+        bail!(
+            "The overlap of {} m does not fit on sheets of {} by {} m",
+            overlap,
+            w,
+            h
+        );
+    }
+
+    let count = |length: i64, size: i64| {
+        let step = size - overlap;
+        ((length - overlap + step - 1) / step).max(1)
+    };
+    let (cols, rows) = (count(area.w(), w), count(area.h(), h));
+
+    // the sheets stick out as much on each side
+    let x = area.x_min() - (cols * (w - overlap) + overlap - area.w()) / 2;
+    let y = area.y_min() - (rows * (h - overlap) + overlap - area.h()) / 2;
+
+    let number = |col: i64, row: i64| {
+        ((0..cols).contains(&col) && (0..rows).contains(&row))
+            .then_some((row * cols + col + 1) as usize)
+    };
+
+    let mut sheets = Vec::new();
+    for row in 0..rows {
+        for col in 0..cols {
+            sheets.push(Sheet {
+                number: number(col, row).unwrap(),
+                select_box: SelectBox::new(
+                    x + col * (w - overlap),
+                    y + (rows - 1 - row) * (h - overlap),
+                    w,
+                    h,
+                ),
+                neighbours: [
+                    number(col, row - 1),
+                    number(col + 1, row),
+                    number(col, row + 1),
+                    number(col - 1, row),
+                ],
+            });
+        }
+    }
+
+    Ok(sheets)
+}
+
+/// The output of a sheet, `atlas.svg` becomes `atlas-1.svg` and
+/// `atlas-index.svg` for the index
+pub fn path(output_path: &str, suffix: &str) -> String {
+    let path = Path::new(output_path);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+    let name = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, suffix, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, suffix),
+    };
+
+    path.with_file_name(name).to_string_lossy().to_string()
+}
+
+/// The number of the sheet with leading zeros to sort the files, as many
+/// digits as the number of sheets: `1` of 4 sheets, `01` of 12
+pub fn suffix(number: usize, count: usize) -> String {
+    format!("{:0width$}", number, width = count.to_string().len())
+}
+
+/// The box of the index over all sheets, on the same paper at a round scale
+/// that fits all sheets
+pub fn index(sheets: &[Sheet], print: Print) -> Result<(SelectBox, Print)> {
+    let x_min = sheets.iter().map(|s| s.select_box.x_min()).min();
+    let y_min = sheets.iter().map(|s| s.select_box.y_min()).min();
+    let x_max = sheets.iter().map(|s| s.select_box.x_max()).max();
+    let y_max = sheets.iter().map(|s| s.select_box.y_max()).max();
+
+    let (Some(x_min), Some(y_min), Some(x_max), Some(y_max)) = (x_min, y_min, x_max, y_max) else {
+        bail!("An atlas without sheets");
+    };
+    let extent = SelectBox::new(x_min, y_min, x_max - x_min, y_max - y_min);
+
+    // metres on a millimetre for the extent inside the margins, rounded up
+    // to a scale of whole thousands
+    let (width, height) = print.page();
+    let mm = f64::max(
+        extent.w() as f64 / (width - 2.0 * print.margin),
+        extent.h() as f64 / (height - 2.0 * print.margin),
+    );
+    let print = Print {
+        scale: Scale(mm.ceil() * 1000.0),
+        ..print
+    };
+
+    let paper = print.select_box(0, 0)?;
+    let select_box = print.select_box(
+        x_min + (extent.w() - paper.w()) / 2,
+        y_min + (extent.h() - paper.h()) / 2,
+    )?;
+
+    Ok((select_box, print))
+}
+
+/// Millimetres of the sheet references and of the numbers on the index
+const REFERENCE: f64 = 3.5;
+const FRAME_NUMBER: f64 = 6.0;
+
+/// The css of the sheet references and the frames on the index, the sizes in
+/// millimetres on paper of `mm` metres
+pub fn css(mm: f64) -> String {
+    let px = |v: f64| geometry::round(v * mm, 3);
+
+    format!(
+        ".sheet-number {{ font-family:sans-serif;font-size:{}px;font-weight:bold }}
+.sheet-reference {{ font-family:sans-serif;font-size:{}px }}
+.sheet-frame {{ fill:none;stroke:black;stroke-width:{} }}
+.sheet-frame-number {{ font-family:sans-serif;font-size:{}px;font-weight:bold;stroke:white;stroke-width:{};paint-order:stroke }}
+",
+        px(REFERENCE),
+        px(REFERENCE),
+        px(0.5),
+        px(FRAME_NUMBER),
+        px(0.8)
+    )
+}
+
+/// The sheet number in the upper left corner and the numbers of the
+/// neighbours in the middle of the edges, in the paper margin when it is
+/// `outside` the map
+pub fn references(
+    sheet: &Sheet,
+    rect: &Rect,
+    mm: f64,
+    outside: bool,
+    taken: &mut Vec<Rect>,
+    precision: u32,
+) -> Vec<String> {
+    let size = REFERENCE * mm;
+    // the middle of the text from the edge, across the edge when outside
+    let offset = if outside { -2.5 * mm } else { mm + size / 2.0 };
+    let inset = if outside { 0.0 } else { mm };
+    let (east, west) = if outside {
+        ((rect.x_max + mm, "start"), (rect.x_min - mm, "end"))
+    } else {
+        ((rect.x_max - mm, "end"), (rect.x_min + mm, "start"))
+    };
+    let (x_mid, y_mid) = (
+        (rect.x_min + rect.x_max) / 2.0,
+        (rect.y_min + rect.y_max) / 2.0,
+    );
+
+    let mut lines = Vec::new();
+
+    let mut text = |(x, y): (f64, f64), anchor: &str, text: String, class: &str| {
+        let width = text_width(&text, size);
+        let x_mid = match anchor {
+            "start" => x + width / 2.0,
+            "end" => x - width / 2.0,
+            _ => x,
+        };
+        if !outside {
+            taken.push(Rect::around((x_mid, y), width, size));
+        }
+
+        lines.push(format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"{}\" dominant-baseline=\"central\" class=\"{}\">{}</text>",
+            geometry::round(x, precision),
+            geometry::round(-y, precision),
+            anchor,
+            class,
+            escape(&text)
+        ));
+    };
+
+    text(
+        (rect.x_min + inset, rect.y_max - offset),
+        "start",
+        format!("Sheet {}", sheet.number),
+        "sheet-number",
+    );
+
+    let [north, east_sheet, south, west_sheet] = sheet.neighbours;
+    if let Some(north) = north {
+        text(
+            (x_mid, rect.y_max - offset),
+            "middle",
+            format!("↑ {}", north),
+            "sheet-reference",
+        );
+    }
+    if let Some(number) = east_sheet {
+        text(
+            (east.0, y_mid),
+            east.1,
+            format!("{} →", number),
+            "sheet-reference",
+        );
+    }
+    if let Some(south) = south {
+        text(
+            (x_mid, rect.y_min + offset),
+            "middle",
+            format!("↓ {}", south),
+            "sheet-reference",
+        );
+    }
+    if let Some(number) = west_sheet {
+        text(
+            (west.0, y_mid),
+            west.1,
+            format!("← {}", number),
+            "sheet-reference",
+        );
+    }
+
+    lines
+}
+
+/// The frames of the sheets on the index with their numbers
+pub fn frames(sheets: &[Sheet], mm: f64, taken: &mut Vec<Rect>, precision: u32) -> Vec<String> {
+    let round = |v: f64| geometry::round(v, precision);
+    let size = FRAME_NUMBER * mm;
+
+    let mut lines = Vec::new();
+
+    for sheet in sheets {
+        let rect = sheet.select_box.rect(0);
+        let center = (
+            (rect.x_min + rect.x_max) / 2.0,
+            (rect.y_min + rect.y_max) / 2.0,
+        );
+
+        lines.push(format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" class=\"sheet-frame\"/>",
+            round(rect.x_min),
+            round(-rect.y_max),
+            round(rect.x_max - rect.x_min),
+            round(rect.y_max - rect.y_min)
+        ));
+        lines.push(format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\" class=\"sheet-frame-number\">{}</text>",
+            round(center.0),
+            round(-center.1),
+            sheet.number
+        ));
+
+        let number = sheet.number.to_string();
+        taken.push(Rect::around(center, text_width(&number, size), size));
+    }
+
+    lines
+}
@@ -0,0 +1,256 @@
+use crate::escape;
+use crate::geometry::{self, Rect};
+use crate::label::text_width;
+use crate::Projection;
+
+/// Millimetres between the furniture and the edge of the map
+pub const MARGIN: f64 = 5.0;
+
+/// Millimetres of the text of the scale bar
+const SCALE_BAR_TEXT: f64 = 3.0;
+
+/// The css class, the millimetres and the weight of the title, date and
+/// attribution
+const CARTOUCHE: [(&str, f64, &str); 3] = [
+    ("cartouche-title", 5.0, "bold"),
+    ("cartouche-date", 3.5, "normal"),
+    ("cartouche-attribution", 3.0, "normal"),
+];
+
+/// The css of the neatline, scale bar, north arrow and cartouche, the sizes
+/// in millimetres on paper of `mm` metres
+pub fn css(mm: f64) -> String {
+    let px = |v: f64| geometry::round(v * mm, 3);
+
+    let mut css = format!(
+        ".neatline {{ fill:none;stroke:black;stroke-width:{} }}
+.scale-bar {{ fill:black;stroke:black;stroke-width:{} }}
+.scale-bar-light {{ fill:white;stroke:black;stroke-width:{} }}
+.scale-bar-label {{ font-family:sans-serif;font-size:{}px }}
+.north-arrow {{ fill:black }}
+.north-arrow-light {{ fill:white;stroke:black;stroke-width:{} }}
+.north-arrow-label {{ font-family:sans-serif;font-size:{}px;font-weight:bold }}
+.cartouche {{ fill:white;stroke:black;stroke-width:{} }}
+",
+        px(0.3),
+        px(0.2),
+        px(0.2),
+        px(SCALE_BAR_TEXT),
+        px(0.2),
+        px(5.0),
+        px(0.3)
+    );
+
+    for (class, size, weight) in CARTOUCHE {
+        css += &format!(
+            ".{} {{ font-family:sans-serif;font-size:{}px;font-weight:{} }}\n",
+            class,
+            px(size),
+            weight
+        );
+    }
+
+    css
+}
+
+/// The frame around the map on the paper
+pub fn neatline(rect: &Rect, precision: u32) -> String {
+    let round = |v: f64| geometry::round(v, precision);
+
+    format!(
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" class=\"neatline\"/>",
+        round(rect.x_min),
+        round(-rect.y_max),
+        round(rect.x_max - rect.x_min),
+        round(rect.y_max - rect.y_min)
+    )
+}
+
+/// The scale bar in the lower left corner: four blocks adding up to a round
+/// number of metres, about a quarter of the map, and the scale as `1:N`.
+/// `mm` is the metres of one millimetre on paper.
+pub fn scale_bar(rect: &Rect, mm: f64, taken: &mut Vec<Rect>, precision: u32) -> Vec<String> {
+    let round = |v: f64| geometry::round(v, precision);
+
+    let length = round_length((rect.x_max - rect.x_min) / 4.0);
+    let height = 1.5 * mm;
+    let font = SCALE_BAR_TEXT * mm;
+    let (x, y) = (rect.x_min + MARGIN * mm, rect.y_min + MARGIN * mm + font);
+
+    let mut lines = Vec::new();
+
+    for i in 0..4 {
+        lines.push(format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" class=\"{}\"/>",
+            round(x + i as f64 * length / 4.0),
+            round(-y - height),
+            round(length / 4.0),
+            round(height),
+            if i % 2 == 0 {
+                "scale-bar"
+            } else {
+                "scale-bar-light"
+            }
+        ));
+    }
+
+    let text = |x: f64, y: f64, anchor: &str, text: &str| {
+        format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"{}\" class=\"scale-bar-label\">{}</text>",
+            round(x),
+            round(-y),
+            anchor,
+            escape(text)
+        )
+    };
+
+    let distance = if length >= 1000.0 {
+        format!("{} km", geometry::round(length / 1000.0, 3))
+    } else {
+        format!("{} m", length)
+    };
+
+    let top = y + height + font / 3.0;
+    lines.push(text(x, top, "middle", "0"));
+    lines.push(text(x + length, top, "middle", &distance));
+    lines.push(text(
+        x,
+        y - font,
+        "start",
+        &format!("1:{}", (mm * 1000.0).round()),
+    ));
+
+    taken.push(Rect {
+        x_min: x - font,
+        y_min: y - 1.5 * font,
+        x_max: x + length + text_width(&distance, font),
+        y_max: top + font,
+    });
+
+    lines
+}
+
+/// 1, 2 or 5 times a power of 10, the largest that is not longer
+fn round_length(max: f64) -> f64 {
+    let power = 10f64.powf(max.log10().floor());
+
+    [5.0, 2.0, 1.0]
+        .into_iter()
+        .map(|n| n * power)
+        .find(|&length| length <= max)
+        .unwrap_or(power)
+}
+
+/// The north arrow in the upper right corner, turned to true north: the
+/// lines of longitude are at an angle with the grid away from the central
+/// meridian of the projection
+pub fn north_arrow(
+    rect: &Rect,
+    mm: f64,
+    projection: &Projection,
+    taken: &mut Vec<Rect>,
+    precision: u32,
+) -> Vec<String> {
+    let round = |v: f64| geometry::round(v, precision);
+
+    let (x, y) = (
+        rect.x_max - (MARGIN + 5.0) * mm,
+        rect.y_max - (MARGIN + 12.0) * mm,
+    );
+    let angle = convergence(projection, x, y);
+
+    // svg coordinates, y down
+    let (sx, sy) = (x, -y);
+    let point = |dx: f64, dy: f64| format!("{} {}", round(sx + dx * mm), round(sy + dy * mm));
+
+    let lines = vec![format!(
+        "<g transform=\"rotate({} {} {})\" class=\"north-arrow\"><path d=\"M {} L {} L {} z\"/><path d=\"M {} L {} L {} z\" class=\"north-arrow-light\"/><text x=\"{}\" y=\"{}\" text-anchor=\"middle\" class=\"north-arrow-label\">N</text></g>",
+        geometry::round(angle, 2),
+        round(sx),
+        round(sy),
+        point(0.0, -6.0),
+        point(-2.5, 6.0),
+        point(0.0, 3.0),
+        point(0.0, -6.0),
+        point(2.5, 6.0),
+        point(0.0, 3.0),
+        round(sx),
+        round(sy - 7.5 * mm)
+    )];
+
+    taken.push(Rect::around((x, y + 2.0 * mm), 12.0 * mm, 22.0 * mm));
+
+    lines
+}
+
+/// Degrees from grid north to true north at the point, clockwise
+fn convergence(projection: &Projection, x: f64, y: f64) -> f64 {
+    let (lat, lon) = projection.unproject(x, y);
+    let (north_x, north_y) = projection.project(lat + 0.01, lon);
+
+    (north_x - x).atan2(north_y - y).to_degrees()
+}
+
+/// The title, date and attribution in a box in the lower right corner, the
+/// lines that are None are left out
+pub fn cartouche(
+    rect: &Rect,
+    mm: f64,
+    lines: [Option<&str>; 3],
+    taken: &mut Vec<Rect>,
+    precision: u32,
+) -> Vec<String> {
+    let round = |v: f64| geometry::round(v, precision);
+
+    let lines: Vec<(&str, f64, &str)> = lines
+        .iter()
+        .zip(CARTOUCHE)
+        .filter_map(|(&text, (class, size, _))| Some((text?, size * mm, class)))
+        .filter(|(text, _, _)| !text.is_empty())
+        .collect();
+
+    if lines.is_empty() {
+        return Vec::new();
+    }
+
+    let padding = 2.0 * mm;
+    let width = lines
+        .iter()
+        .map(|&(text, size, _)| text_width(text, size))
+        .fold(0.0, f64::max)
+        + 2.0 * padding;
+    let height = lines.iter().map(|&(_, size, _)| 1.3 * size).sum::<f64>() + 2.0 * padding;
+
+    let x_max = rect.x_max - MARGIN * mm;
+    let y_min = rect.y_min + MARGIN * mm;
+    let (x_min, y_max) = (x_max - width, y_min + height);
+
+    let mut svg = vec![format!(
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" class=\"cartouche\"/>",
+        round(x_min),
+        round(-y_max),
+        round(width),
+        round(height)
+    )];
+
+    let mut y = y_max - padding;
+    for (text, size, class) in lines {
+        y -= 1.3 * size;
+        svg.push(format!(
+            "<text x=\"{}\" y=\"{}\" class=\"{}\">{}</text>",
+            round(x_min + padding),
+            round(-y - 0.3 * size),
+            class,
+            escape(text)
+        ));
+    }
+
+    taken.push(Rect {
+        x_min,
+        y_min,
+        x_max,
+        y_max,
+    });
+
+    svg
+}
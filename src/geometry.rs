@@ -0,0 +1,498 @@
+pub type Point = (f64, f64);
+
+/// Axis aligned rectangle in map coordinates
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Rect {
+    pub x_min: f64,
+    pub y_min: f64,
+    pub x_max: f64,
+    pub y_max: f64,
+}
+
+impl Rect {
+    pub fn center(&self) -> Point {
+        (
+            (self.x_min + self.x_max) / 2.0,
+            (self.y_min + self.y_max) / 2.0,
+        )
+    }
+
+    pub fn contains(&self, (x, y): Point) -> bool {
+        x >= self.x_min && x <= self.x_max && y >= self.y_min && y <= self.y_max
+    }
+
+    /// A rectangle of width w and height h around the center
+    pub fn around((x, y): Point, w: f64, h: f64) -> Rect {
+        Rect {
+            x_min: x - w / 2.0,
+            y_min: y - h / 2.0,
+            x_max: x + w / 2.0,
+            y_max: y + h / 2.0,
+        }
+    }
+
+    /// true when the rectangles overlap
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.x_min < other.x_max
+            && other.x_min < self.x_max
+            && self.y_min < other.y_max
+            && other.y_min < self.y_max
+    }
+
+    /// true when the rectangles overlap or only share an edge
+    pub fn touches(&self, other: &Rect) -> bool {
+        self.x_min <= other.x_max
+            && other.x_min <= self.x_max
+            && self.y_min <= other.y_max
+            && other.y_min <= self.y_max
+    }
+
+    /// The smallest rectangle around the points, None without points
+    pub fn bounds(points: &[Point]) -> Option<Rect> {
+        let (&(x, y), rest) = points.split_first()?;
+
+        Some(rest.iter().fold(
+            Rect {
+                x_min: x,
+                y_min: y,
+                x_max: x,
+                y_max: y,
+            },
+            |r, &(x, y)| Rect {
+                x_min: r.x_min.min(x),
+                y_min: r.y_min.min(y),
+                x_max: r.x_max.max(x),
+                y_max: r.y_max.max(y),
+            },
+        ))
+    }
+
+    /// true when the other rectangle is completely inside this one
+    pub fn covers(&self, other: &Rect) -> bool {
+        self.contains((other.x_min, other.y_min)) && self.contains((other.x_max, other.y_max))
+    }
+
+    /// Liang–Barsky, the part of the segment inside the rectangle
+    pub fn clip_segment(&self, a: Point, b: Point) -> Option<(Point, Point)> {
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let mut t0: f64 = 0.0;
+        let mut t1: f64 = 1.0;
+
+        for (p, q) in [
+            (-dx, a.0 - self.x_min),
+            (dx, self.x_max - a.0),
+            (-dy, a.1 - self.y_min),
+            (dy, self.y_max - a.1),
+        ] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return None;
+                }
+            } else {
+                let t = q / p;
+                if p < 0.0 {
+                    t0 = t0.max(t);
+                } else {
+                    t1 = t1.min(t);
+                }
+            }
+        }
+
+        if t0 > t1 {
+            return None;
+        }
+
+        let at = |t: f64| {
+            if t == 0.0 {
+                a
+            } else if t == 1.0 {
+                b
+            } else {
+                (a.0 + t * dx, a.1 + t * dy)
+            }
+        };
+
+        Some((at(t0), at(t1)))
+    }
+}
+
+/// true when a node of the line is inside the rectangle or a segment crosses it
+pub fn line_intersects(line: &[Point], rect: &Rect) -> bool {
+    line.iter().any(|&p| rect.contains(p))
+        || line
+            .windows(2)
+            .any(|segment| rect.clip_segment(segment[0], segment[1]).is_some())
+}
+
+/// true when the border of the area crosses the rectangle or the area covers
+/// the whole rectangle, the rings are combined with the even-odd rule
+pub fn area_intersects<R: AsRef<[Point]>>(rings: &[R], rect: &Rect) -> bool {
+    rings
+        .iter()
+        .any(|ring| line_intersects(ring.as_ref(), rect))
+        || rings
+            .iter()
+            .filter(|ring| ring_contains(ring.as_ref(), rect.center()))
+            .count()
+            % 2
+            == 1
+}
+
+/// Point in polygon with ray casting
+pub fn ring_contains(ring: &[Point], (x, y): Point) -> bool {
+    let mut inside = false;
+
+    for segment in ring.windows(2) {
+        let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+        if (y0 > y) != (y1 > y) && x < x0 + (y - y0) * (x1 - x0) / (y1 - y0) {
+            inside = !inside;
+        }
+    }
+
+    inside
+}
+
+/// Clip a polyline, every time the line leaves the rectangle a new part starts
+pub fn clip_line(line: &[Point], rect: &Rect) -> Vec<Vec<Point>> {
+    let mut parts = Vec::new();
+    let mut part: Vec<Point> = Vec::new();
+
+    for segment in line.windows(2) {
+        if let Some((a, b)) = rect.clip_segment(segment[0], segment[1]) {
+            if part.last() != Some(&a) {
+                if part.len() > 1 {
+                    parts.push(std::mem::take(&mut part));
+                }
+                part = vec![a];
+            }
+            part.push(b);
+        }
+    }
+
+    if part.len() > 1 {
+        parts.push(part);
+    }
+
+    parts
+}
+
+/// Clip a closed ring (first point equal to the last) with Sutherland–Hodgman.
+///
+/// The result is again a closed ring, or empty when nothing is left.
+pub fn clip_polygon(ring: &[Point], rect: &Rect) -> Vec<Point> {
+    let mut points: Vec<Point> = ring.to_vec();
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+
+    for edge in 0..4 {
+        let inside = |p: Point| match edge {
+            0 => p.0 >= rect.x_min,
+            1 => p.0 <= rect.x_max,
+            2 => p.1 >= rect.y_min,
+            _ => p.1 <= rect.y_max,
+        };
+        let intersect = |a: Point, b: Point| match edge {
+            0 => at_x(a, b, rect.x_min),
+            1 => at_x(a, b, rect.x_max),
+            2 => at_y(a, b, rect.y_min),
+            _ => at_y(a, b, rect.y_max),
+        };
+
+        let input = std::mem::take(&mut points);
+        for (i, &b) in input.iter().enumerate() {
+            let a = input[(i + input.len() - 1) % input.len()];
+            match (inside(a), inside(b)) {
+                (true, true) => points.push(b),
+                (true, false) => points.push(intersect(a, b)),
+                (false, true) => {
+                    points.push(intersect(a, b));
+                    points.push(b);
+                }
+                (false, false) => {}
+            }
+        }
+    }
+
+    if points.len() < 3 {
+        return Vec::new();
+    }
+
+    points.push(points[0]);
+    points
+}
+
+fn at_x(a: Point, b: Point, x: f64) -> Point {
+    (x, a.1 + (b.1 - a.1) * (x - a.0) / (b.0 - a.0))
+}
+
+fn at_y(a: Point, b: Point, y: f64) -> Point {
+    (a.0 + (b.0 - a.0) * (y - a.1) / (b.1 - a.1), y)
+}
+
+/// Douglas–Peucker, drop the points closer than the tolerance to the line
+/// through their neighbours that are kept
+pub fn simplify(points: &[Point], tolerance: f64) -> Vec<Point> {
+    if points.len() < 3 || tolerance <= 0.0 {
+        return points.to_vec();
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let mut max = 0.0;
+        let mut index = first;
+        for (i, &p) in points.iter().enumerate().take(last).skip(first + 1) {
+            let d = segment_distance(p, points[first], points[last]);
+            if d > max {
+                max = d;
+                index = i;
+            }
+        }
+
+        if max > tolerance {
+            keep[index] = true;
+            stack.push((first, index));
+            stack.push((index, last));
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter(|&(_, keep)| keep)
+        .map(|(&p, _)| p)
+        .collect()
+}
+
+/// length of a line
+pub fn length(line: &[Point]) -> f64 {
+    line.windows(2)
+        .map(|s| ((s[1].0 - s[0].0).powi(2) + (s[1].1 - s[0].1).powi(2)).sqrt())
+        .sum()
+}
+
+/// The point at a distance along the line
+pub fn point_at(line: &[Point], distance: f64) -> Option<Point> {
+    let mut rest = distance;
+
+    for s in line.windows(2) {
+        let length = (s[1].0 - s[0].0).hypot(s[1].1 - s[0].1);
+        if rest <= length && length > 0.0 {
+            let t = rest / length;
+            return Some((
+                s[0].0 + t * (s[1].0 - s[0].0),
+                s[0].1 + t * (s[1].1 - s[0].1),
+            ));
+        }
+        rest -= length;
+    }
+
+    line.last().copied()
+}
+
+/// surface of a ring with the shoelace formula
+pub fn area(ring: &[Point]) -> f64 {
+    ring.windows(2)
+        .map(|s| s[0].0 * s[1].1 - s[1].0 * s[0].1)
+        .sum::<f64>()
+        .abs()
+        / 2.0
+}
+
+/// A square of the search for the pole of inaccessibility
+struct Cell {
+    center: Point,
+    /// half the size
+    h: f64,
+    /// distance to the border, negative outside the area
+    d: f64,
+    /// the largest distance possible in the cell
+    max: f64,
+}
+
+impl Cell {
+    fn new<R: AsRef<[Point]>>(center: Point, h: f64, rings: &[R]) -> Cell {
+        let d = border_distance(center, rings);
+        Cell {
+            center,
+            h,
+            d,
+            max: d + h * std::f64::consts::SQRT_2,
+        }
+    }
+}
+
+impl PartialEq for Cell {
+    fn eq(&self, other: &Cell) -> bool {
+        self.max == other.max
+    }
+}
+
+impl Eq for Cell {}
+
+impl PartialOrd for Cell {
+    fn partial_cmp(&self, other: &Cell) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Cell {
+    fn cmp(&self, other: &Cell) -> std::cmp::Ordering {
+        self.max.total_cmp(&other.max)
+    }
+}
+
+/// Pole of inaccessibility, the point inside the area farthest from the
+/// border and that distance. Found with the polylabel algorithm of Mapbox:
+/// split the squares that can hold a better point until the result is
+/// within `precision`.
+pub fn pole_of_inaccessibility<R: AsRef<[Point]>>(
+    rings: &[R],
+    precision: f64,
+) -> Option<(Point, f64)> {
+    let mut points = rings.iter().flat_map(|ring| ring.as_ref().iter());
+    let first = *points.next()?;
+
+    let bounds = points.fold(
+        Rect {
+            x_min: first.0,
+            y_min: first.1,
+            x_max: first.0,
+            y_max: first.1,
+        },
+        |r, &(x, y)| Rect {
+            x_min: r.x_min.min(x),
+            y_min: r.y_min.min(y),
+            x_max: r.x_max.max(x),
+            y_max: r.y_max.max(y),
+        },
+    );
+
+    let size = (bounds.x_max - bounds.x_min).min(bounds.y_max - bounds.y_min);
+    if size <= 0.0 {
+        return Some((first, 0.0));
+    }
+
+    let mut queue = std::collections::BinaryHeap::new();
+    let h = size / 2.0;
+    let mut x = bounds.x_min;
+    while x < bounds.x_max {
+        let mut y = bounds.y_min;
+        while y < bounds.y_max {
+            queue.push(Cell::new((x + h, y + h), h, rings));
+            y += size;
+        }
+        x += size;
+    }
+
+    let center = bounds.center();
+    let mut best = (center, border_distance(center, rings));
+
+    while let Some(cell) = queue.pop() {
+        if cell.d > best.1 {
+            best = (cell.center, cell.d);
+        }
+
+        if cell.max - best.1 <= precision {
+            continue;
+        }
+
+        let h = cell.h / 2.0;
+        let (x, y) = cell.center;
+        for center in [
+            (x - h, y - h),
+            (x + h, y - h),
+            (x - h, y + h),
+            (x + h, y + h),
+        ] {
+            queue.push(Cell::new(center, h, rings));
+        }
+    }
+
+    Some(best)
+}
+
+/// distance from p to the nearest border, negative outside the area
+fn border_distance<R: AsRef<[Point]>>(p: Point, rings: &[R]) -> f64 {
+    let inside = rings
+        .iter()
+        .filter(|ring| ring_contains(ring.as_ref(), p))
+        .count()
+        % 2
+        == 1;
+
+    let distance = rings
+        .iter()
+        .flat_map(|ring| ring.as_ref().windows(2))
+        .map(|s| segment_distance(p, s[0], s[1]))
+        .fold(f64::INFINITY, f64::min);
+
+    if inside {
+        distance
+    } else {
+        -distance
+    }
+}
+
+/// distance from p to the segment a b
+fn segment_distance(p: Point, a: Point, b: Point) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+
+    let t = if length == 0.0 {
+        0.0
+    } else {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length).clamp(0.0, 1.0)
+    };
+
+    ((a.0 + t * dx - p.0).powi(2) + (a.1 + t * dy - p.1).powi(2)).sqrt()
+}
+
+/// svg path definition, the y axis of the svg points down.
+///
+/// Every part starts with an absolute `M`, the other points are relative `l`
+/// steps, rounded to `precision` decimals. Points that are equal after
+/// rounding are dropped and a part that ends where it starts is closed with
+/// `z`.
+pub fn svg_path_d(parts: &[Vec<Point>], precision: u32) -> String {
+    let round = |v: f64| round(v, precision);
+    let mut d = String::new();
+
+    for part in parts {
+        let mut points: Vec<(f64, f64)> =
+            part.iter().map(|&(x, y)| (round(x), round(-y))).collect();
+        points.dedup();
+
+        if points.len() < 2 {
+            continue;
+        }
+
+        let closed = points.len() > 2 && points.first() == points.last();
+        if closed {
+            points.pop();
+        }
+
+        d += &format!("M {} {} l", points[0].0, points[0].1);
+        for step in points.windows(2) {
+            d += &format!(
+                " {} {}",
+                round(step[1].0 - step[0].0),
+                round(step[1].1 - step[0].1)
+            );
+        }
+
+        d += if closed { " z " } else { " " };
+    }
+
+    d
+}
+
+/// round to a number of decimals
+pub fn round(v: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    (v * factor).round() / factor
+}
@@ -0,0 +1,249 @@
+use crate::escape;
+use crate::geometry::{self, Point, Rect};
+use crate::label::text_width;
+use crate::style::font_size;
+use crate::Projection;
+use anyhow::{bail, Result};
+
+/// Points on a line of latitude or longitude, enough to follow the curve
+const STEPS: usize = 64;
+
+/// More lines than this across the map is a spacing by mistake
+const MAX_LINES: f64 = 1000.0;
+
+/// Lines of the projection every `spacing` metres, with the kilometres at
+/// the edges of the box. The labels take room in `taken`.
+pub fn grid(
+    clip: &Rect,
+    spacing: f64,
+    label_style: &str,
+    taken: &mut Vec<Rect>,
+    precision: u32,
+) -> Result<Vec<String>> {
+    let extent = (clip.x_max - clip.x_min).max(clip.y_max - clip.y_min);
+    check_spacing(extent, spacing, "m")?;
+
+    let mut lines = Vec::new();
+    let mut labels = Vec::new();
+
+    for x in steps(clip.x_min, clip.x_max, spacing) {
+        let line = vec![(x, clip.y_min), (x, clip.y_max)];
+        let text = km(x);
+
+        for &point in &line {
+            labels.extend(edge_label(
+                point,
+                &text,
+                ("grid-label", label_style),
+                clip,
+                taken,
+                precision,
+            ));
+        }
+
+        lines.push(path(&[line], "grid", precision));
+    }
+
+    for y in steps(clip.y_min, clip.y_max, spacing) {
+        let line = vec![(clip.x_min, y), (clip.x_max, y)];
+        let text = km(y);
+
+        for &point in &line {
+            labels.extend(edge_label(
+                point,
+                &text,
+                ("grid-label", label_style),
+                clip,
+                taken,
+                precision,
+            ));
+        }
+
+        lines.push(path(&[line], "grid", precision));
+    }
+
+    lines.extend(labels);
+    Ok(lines)
+}
+
+/// Lines of latitude and longitude every `step` degrees, curved as the
+/// projection bends them, with the degrees where they leave the box
+pub fn graticule(
+    clip: &Rect,
+    step: f64,
+    projection: &Projection,
+    label_style: &str,
+    taken: &mut Vec<Rect>,
+    precision: u32,
+) -> Result<Vec<String>> {
+    // the extent in degrees, from points all around the border of the box
+    let border: Vec<(f64, f64)> = (0..STEPS)
+        .flat_map(|i| {
+            let t = i as f64 / STEPS as f64;
+            let x = clip.x_min + t * (clip.x_max - clip.x_min);
+            let y = clip.y_min + t * (clip.y_max - clip.y_min);
+            [
+                (x, clip.y_min),
+                (x, clip.y_max),
+                (clip.x_min, y),
+                (clip.x_max, y),
+            ]
+        })
+        .map(|(x, y)| projection.unproject(x, y))
+        .collect();
+
+    let (mut lat_min, mut lat_max) = (f64::MAX, f64::MIN);
+    let (mut lon_min, mut lon_max) = (f64::MAX, f64::MIN);
+    for &(lat, lon) in &border {
+        (lat_min, lat_max) = (lat_min.min(lat), lat_max.max(lat));
+        (lon_min, lon_max) = (lon_min.min(lon), lon_max.max(lon));
+    }
+
+    check_spacing((lat_max - lat_min).max(lon_max - lon_min), step, "°")?;
+
+    let decimals = (-step.log10()).ceil().max(0.0) as usize;
+
+    let mut lines = Vec::new();
+    let mut labels = Vec::new();
+
+    let mut curve = |points: Vec<Point>, text: String| {
+        let parts = geometry::clip_line(&points, clip);
+
+        for part in &parts {
+            for point in [part.first(), part.last()].into_iter().flatten() {
+                labels.extend(edge_label(
+                    *point,
+                    &text,
+                    ("graticule-label", label_style),
+                    clip,
+                    taken,
+                    precision,
+                ));
+            }
+        }
+
+        if !parts.is_empty() {
+            lines.push(path(&parts, "graticule", precision));
+        }
+    };
+
+    for lon in steps(lon_min, lon_max, step) {
+        let points = (0..=STEPS)
+            .map(|i| lat_min + (lat_max - lat_min) * i as f64 / STEPS as f64)
+            .map(|lat| projection.project(lat, lon))
+            .collect();
+        let text = format!(
+            "{:.*}°{}",
+            decimals,
+            lon.abs(),
+            if lon < 0.0 { "W" } else { "E" }
+        );
+
+        curve(points, text);
+    }
+
+    for lat in steps(lat_min, lat_max, step) {
+        let points = (0..=STEPS)
+            .map(|i| lon_min + (lon_max - lon_min) * i as f64 / STEPS as f64)
+            .map(|lon| projection.project(lat, lon))
+            .collect();
+        let text = format!(
+            "{:.*}°{}",
+            decimals,
+            lat.abs(),
+            if lat < 0.0 { "S" } else { "N" }
+        );
+
+        curve(points, text);
+    }
+
+    lines.extend(labels);
+    Ok(lines)
+}
+
+/// An error when the spacing gives more than `MAX_LINES` lines over the
+/// extent
+fn check_spacing(extent: f64, spacing: f64, unit: &str) -> Result<()> {
+    let count = extent / spacing;
+
+    if !count.is_finite() || count > MAX_LINES {
+        bail!(
+            "A spacing of {}{} gives more than {} lines across the map, use a wider spacing",
+            spacing,
+            unit,
+            MAX_LINES
+        );
+    }
+
+    Ok(())
+}
+
+/// The multiples of `step` between `min` and `max`
+fn steps(min: f64, max: f64, step: f64) -> impl Iterator<Item = f64> {
+    let first = (min / step).ceil() as i64;
+    let last = (max / step).floor() as i64;
+
+    (first..=last).map(move |i| i as f64 * step)
+}
+
+/// Metres as kilometres, `170500` becomes `170.5`
+fn km(metres: f64) -> String {
+    geometry::round(metres / 1000.0, 3).to_string()
+}
+
+fn path(parts: &[Vec<Point>], class: &str, precision: u32) -> String {
+    format!(
+        "<path d=\"{}\" class=\"{}\"/>",
+        geometry::svg_path_d(parts, precision),
+        class
+    )
+}
+
+/// The text just inside the edge of the box the point is on, None when the
+/// point is not on an edge or the text does not fit
+fn edge_label(
+    (x, y): Point,
+    text: &str,
+    (class, style): (&str, &str),
+    clip: &Rect,
+    taken: &mut Vec<Rect>,
+    precision: u32,
+) -> Option<String> {
+    let size = font_size(style);
+    let (w, h) = (text_width(text, size), size);
+    let gap = size / 4.0;
+    let near = |a: f64, b: f64| (a - b).abs() < 0.01;
+
+    let (center, anchor) = if near(y, clip.y_max) {
+        ((x, y - gap - h / 2.0), "middle")
+    } else if near(y, clip.y_min) {
+        ((x, y + gap + h / 2.0), "middle")
+    } else if near(x, clip.x_min) {
+        ((x + gap + w / 2.0, y), "start")
+    } else if near(x, clip.x_max) {
+        ((x - gap - w / 2.0, y), "end")
+    } else {
+        return None;
+    };
+
+    let rect = Rect::around(center, w, h);
+    if !clip.covers(&rect) || taken.iter().any(|t| t.intersects(&rect)) {
+        return None;
+    }
+    taken.push(rect);
+
+    let x = match anchor {
+        "start" => rect.x_min,
+        "end" => rect.x_max,
+        _ => center.0,
+    };
+
+    Some(format!(
+        "<text x=\"{}\" y=\"{}\" class=\"{}\" text-anchor=\"{}\" dominant-baseline=\"central\">{}</text>",
+        geometry::round(x, precision),
+        geometry::round(-center.1, precision),
+        class,
+        anchor,
+        escape(text)
+    ))
+}
@@ -0,0 +1,174 @@
+use crate::escape;
+use crate::geometry::{self, Point, Rect};
+use crate::style::{self, Label};
+use log::debug;
+
+/// Estimated width of a text, an average letter is 0.6 of the font size
+pub fn text_width(text: &str, font_size: f64) -> f64 {
+    text.chars().count() as f64 * 0.6 * font_size
+}
+
+/// The text style with the halo drawn beneath the letters
+pub fn text_style(label: &Label) -> String {
+    match label.halo {
+        Some(halo) => format!(
+            "{};{};stroke-linejoin:round;paint-order:stroke",
+            label.style.trim_end_matches(';'),
+            halo.trim_end_matches(';')
+        ),
+        None => label.style.to_string(),
+    }
+}
+
+/// The name along the longest part of a way, from left to right so it is
+/// never upside down. None when the part is shorter than the name.
+pub fn way_label(
+    id: i64,
+    name: &str,
+    parts: &[Vec<Point>],
+    label: &Label,
+    precision: u32,
+) -> Option<String> {
+    let longest = parts.iter().max_by(|a, b| {
+        geometry::length(a)
+            .partial_cmp(&geometry::length(b))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    })?;
+
+    if geometry::length(longest) < text_width(name, style::font_size(label.style)) {
+        return None;
+    }
+
+    let mut path = longest.clone();
+    if path.last()?.0 < path.first()?.0 {
+        path.reverse();
+    }
+
+    Some(format!(
+        "<defs><path id=\"label-{}\" d=\"{}\"/></defs><text class=\"{}-label\" dominant-baseline=\"central\"><textPath xlink:href=\"#label-{}\" startOffset=\"50%\" text-anchor=\"middle\">{}</textPath></text>",
+        id,
+        geometry::svg_path_d(&[path], precision),
+        label.class,
+        id,
+        escape(name)
+    ))
+}
+
+/// A name at a point: a place, a poi or the inside of an area
+#[derive(Debug, Clone)]
+pub struct Text {
+    /// the id of the element, the text gets the id `label-{id}`
+    pub id: String,
+    pub text: String,
+    pub point: Point,
+    /// half the size of the symbol on the point, the text goes next to it
+    pub symbol: f64,
+    /// css class of the label
+    pub class: String,
+    pub font_size: f64,
+    pub importance: u32,
+    /// surface of the area, larger areas go first
+    pub area: f64,
+}
+
+impl Text {
+    pub fn new(id: String, text: &str, point: Point, label: &Label) -> Text {
+        Text {
+            id,
+            text: text.to_string(),
+            point,
+            symbol: 0.0,
+            class: format!("{}-label", label.class),
+            font_size: style::font_size(label.style),
+            importance: label.importance,
+            area: 0.0,
+        }
+    }
+
+    /// The boxes the text can go in, the best first
+    fn candidates(&self) -> Vec<Rect> {
+        let (x, y) = self.point;
+        let w = text_width(&self.text, self.font_size);
+        let h = self.font_size;
+
+        if self.symbol > 0.0 {
+            // right, left, below and above the symbol
+            let dx = self.symbol + h / 4.0 + w / 2.0;
+            let dy = self.symbol + h / 4.0 + h / 2.0;
+            vec![
+                Rect::around((x + dx, y), w, h),
+                Rect::around((x - dx, y), w, h),
+                Rect::around((x, y - dy), w, h),
+                Rect::around((x, y + dy), w, h),
+            ]
+        } else {
+            // on the point, or shifted up or down a line
+            vec![
+                Rect::around((x, y), w, h),
+                Rect::around((x, y + h), w, h),
+                Rect::around((x, y - h), w, h),
+            ]
+        }
+    }
+
+    fn svg(&self, rect: &Rect, precision: u32) -> String {
+        let (x, y) = rect.center();
+
+        format!(
+            "<text x=\"{}\" y=\"{}\" class=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\" id=\"label-{}\">{}</text>",
+            geometry::round(x, precision),
+            geometry::round(-y, precision),
+            self.class,
+            self.id,
+            escape(&self.text)
+        )
+    }
+}
+
+pub struct Texts(Vec<Text>);
+
+impl Texts {
+    pub fn new() -> Texts {
+        Texts(Vec::new())
+    }
+
+    pub fn one(text: Text) -> Texts {
+        Texts(vec![text])
+    }
+
+    pub fn combine(mut self, other: Texts) -> Texts {
+        self.0.extend(other.0);
+        self
+    }
+
+    /// Place the texts in the box without overlapping each other or what is
+    /// already taken, the most important and largest areas first. A text that
+    /// overlaps in every position is left out.
+    pub fn place(mut self, taken: &mut Vec<Rect>, clip: &Rect, precision: u32) -> Vec<String> {
+        self.0.sort_by(|a, b| {
+            b.importance
+                .cmp(&a.importance)
+                .then(b.area.total_cmp(&a.area))
+                .then(a.id.cmp(&b.id))
+        });
+
+        let mut lines = Vec::new();
+
+        for text in &self.0 {
+            let place = text
+                .candidates()
+                .into_iter()
+                .find(|c| clip.covers(c) && !taken.iter().any(|t| t.intersects(c)));
+
+            match place {
+                Some(rect) => {
+                    lines.push(text.svg(&rect, precision));
+                    taken.push(rect);
+                }
+                None => debug!("No room for label {} {}", text.id, text.text),
+            }
+        }
+
+        lines
+    }
+}
@@ -0,0 +1,182 @@
+use crate::escape;
+use crate::furniture::MARGIN;
+use crate::geometry::{self, Rect};
+use crate::label::text_width;
+use crate::style::{LegendEntry, Swatch};
+use crate::symbol::symbol_id;
+
+/// Millimetres of the swatches
+const SWATCH: (f64, f64) = (8.0, 4.0);
+
+/// Millimetres of the title and the captions
+const TITLE: f64 = 4.0;
+const CAPTION: f64 = 3.0;
+
+/// The css of the box, the title and the captions, the sizes in millimetres
+/// on paper of `mm` metres
+pub fn css(mm: f64) -> String {
+    let px = |v: f64| geometry::round(v * mm, 3);
+
+    format!(
+        ".legend {{ fill:white;stroke:black;stroke-width:{} }}
+.legend-title {{ font-family:sans-serif;font-size:{}px;font-weight:bold }}
+.legend-caption {{ font-family:sans-serif;font-size:{}px }}
+",
+        px(0.3),
+        px(TITLE),
+        px(CAPTION)
+    )
+}
+
+/// The legend in a box in the upper left corner, a swatch and the caption
+/// for every entry, in more columns when it does not fit in the height of
+/// the map
+pub fn legend(
+    rect: &Rect,
+    mm: f64,
+    entries: &[LegendEntry],
+    taken: &mut Vec<Rect>,
+    precision: u32,
+) -> Vec<String> {
+    if entries.is_empty() {
+        return Vec::new();
+    }
+
+    let round = |v: f64| geometry::round(v, precision);
+
+    let padding = 2.0 * mm;
+    let title = TITLE * mm;
+    let size = CAPTION * mm;
+    let row = 6.0 * mm;
+    let (swatch_width, swatch_height) = (SWATCH.0 * mm, SWATCH.1 * mm);
+
+    let header = 1.5 * title;
+    let room = rect.y_max - rect.y_min - 2.0 * MARGIN * mm - 2.0 * padding - header;
+    let rows = ((room / row).floor() as usize).clamp(1, entries.len());
+    let columns: Vec<&[LegendEntry]> = entries.chunks(rows).collect();
+
+    let widths: Vec<f64> = columns
+        .iter()
+        .map(|column| {
+            column
+                .iter()
+                .map(|entry| text_width(&entry.caption, size))
+                .fold(0.0, f64::max)
+                + swatch_width
+                + 2.0 * mm
+        })
+        .collect();
+
+    let width = widths.iter().sum::<f64>() + 4.0 * mm * (widths.len() - 1) as f64 + 2.0 * padding;
+    let height = 2.0 * padding + header + rows as f64 * row;
+
+    let x_min = rect.x_min + MARGIN * mm;
+    let y_max = rect.y_max - MARGIN * mm;
+
+    let mut lines = vec![
+        format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" class=\"legend\"/>",
+            round(x_min),
+            round(-y_max),
+            round(width),
+            round(height)
+        ),
+        format!(
+            "<text x=\"{}\" y=\"{}\" class=\"legend-title\">Legend</text>",
+            round(x_min + padding),
+            round(-(y_max - padding - title))
+        ),
+    ];
+
+    let mut x = x_min + padding;
+
+    for (column, column_width) in columns.iter().zip(&widths) {
+        for (i, entry) in column.iter().enumerate() {
+            let y = y_max - padding - header - (i as f64 + 0.5) * row;
+
+            lines.extend(swatch(
+                entry,
+                (x, y),
+                (swatch_width, swatch_height),
+                precision,
+            ));
+            lines.push(format!(
+                "<text x=\"{}\" y=\"{}\" dominant-baseline=\"central\" class=\"legend-caption\">{}</text>",
+                round(x + swatch_width + 2.0 * mm),
+                round(-y),
+                escape(&entry.caption)
+            ));
+        }
+
+        x += column_width + 4.0 * mm;
+    }
+
+    taken.push(Rect {
+        x_min,
+        y_min: y_max - height,
+        x_max: x_min + width,
+        y_max,
+    });
+
+    lines
+}
+
+/// The sample of a rule with its left side at x, around y
+pub fn swatch(
+    entry: &LegendEntry,
+    (x, y): (f64, f64),
+    (w, h): (f64, f64),
+    precision: u32,
+) -> Vec<String> {
+    let round = |v: f64| geometry::round(v, precision);
+    let line = |class: String| {
+        format!(
+            "<path d=\"M {} {} L {} {}\" class=\"{}\"/>",
+            round(x),
+            round(-y),
+            round(x + w),
+            round(-y),
+            class
+        )
+    };
+
+    match entry.swatch {
+        Swatch::Area => vec![format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" class=\"{}\"/>",
+            round(x),
+            round(-y - h / 2.0),
+            round(w),
+            round(h),
+            entry.class
+        )],
+        Swatch::Line { casing } => {
+            let mut lines = Vec::new();
+            if casing {
+                lines.push(line(format!("{}-casing", entry.class)));
+            }
+            lines.push(line(entry.class.to_string()));
+            lines
+        }
+        Swatch::Symbol { name, class } => {
+            let size = 1.25 * h;
+            let mut line = format!(
+                "<use xlink:href=\"#{}\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"",
+                symbol_id(name),
+                round(x + (w - size) / 2.0),
+                round(-y - size / 2.0),
+                round(size),
+                round(size)
+            );
+            if class {
+                line += &format!(" class=\"{}\"", entry.class);
+            }
+            vec![line + "/>"]
+        }
+        Swatch::Text => vec![format!(
+            "<text x=\"{}\" y=\"{}\" class=\"{}-label\" text-anchor=\"middle\" dominant-baseline=\"central\">Abc</text>",
+            round(x + w / 2.0),
+            round(-y),
+            entry.class
+        )],
+    }
+}
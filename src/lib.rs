@@ -1,242 +1,1154 @@
+mod atlas;
+mod furniture;
+mod geometry;
+mod grid;
+mod label;
+mod legend;
+mod multipolygon;
 mod node_index;
+mod node_store;
+mod paper;
+mod pattern;
+mod pdf;
+mod preview;
+mod projection;
+mod shield;
 mod style;
+mod svg_file;
+mod symbol;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use geometry::Rect;
+use label::{Text, Texts};
 use log::debug;
-use node_index::NodeIndex;
+use multipolygon::{MemberWays, MultiPolygons};
+pub use node_store::NodeStore;
+use node_store::Nodes;
 use osmpbf::{Element, ElementReader};
-use std::collections::HashMap;
-use std::fmt::{Display, Formatter};
-use std::io::Write;
-use style::Style;
+pub use paper::{Orientation, Paper, Print, Scale};
+pub use projection::Projection;
+use shield::Shields;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use style::{Label, WayStyle};
+use style::{Style, FURNITURE, GRID, LABELS};
+use symbol::{Poi, Pois};
 
-struct Svg(HashMap<u32, Vec<String>>);
+/// Drawing order of the svg lines
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Order {
+    /// the index of the layer group in [Style::layers]
+    group: usize,
+    /// bridges, with an OSM layer above 0, go over everything on the ground
+    /// of the group
+    level: i32,
+    z_index: u32,
+    /// tunnels and the other OSM layers within the z-index
+    layer: i32,
+    /// the casings of a layer go beneath the fills
+    fill: bool,
+}
+
+impl Order {
+    /// A line on OSM layer `layer` of a group, drawn as casing or fill
+    fn line(group: usize, z_index: u32, layer: i32, fill: bool) -> Order {
+        Order {
+            group,
+            level: layer.max(0),
+            z_index,
+            layer,
+            fill,
+        }
+    }
+
+    /// In a group without z-index, for the grid and the labels
+    fn group(group: usize) -> Order {
+        Order::line(group, 0, 0, true)
+    }
+}
+
+struct Svg(HashMap<Order, Vec<String>>);
 
 impl Svg {
     pub fn new() -> Svg {
         Svg(HashMap::new())
     }
 
-    pub fn one(prio: u32, svg_line: String) -> Svg {
-        Svg(HashMap::from([(prio, vec![svg_line])]))
+    pub fn one(order: Order, svg_line: String) -> Svg {
+        Svg(HashMap::from([(order, vec![svg_line])]))
     }
 
     pub fn combine(mut self, other: Svg) -> Svg {
-        for (prio, svg_lines) in other.0 {
-            if let Some(e) = self.0.get_mut(&prio) {
+        for (order, svg_lines) in other.0 {
+            if let Some(e) = self.0.get_mut(&order) {
                 e.extend(svg_lines);
             } else {
-                self.0.insert(prio, svg_lines);
+                self.0.insert(order, svg_lines);
             }
         }
         self
     }
 
-    pub fn append_line(&mut self, prio: u32, svg_line: String) {
-        if let Some(e) = self.0.get_mut(&prio) {
+    pub fn append_line(&mut self, order: Order, svg_line: String) {
+        if let Some(e) = self.0.get_mut(&order) {
             e.push(svg_line);
         } else {
-            self.0.insert(prio, vec![svg_line]);
+            self.0.insert(order, vec![svg_line]);
         }
     }
-}
 
-impl Display for Svg {
-    fn fmt<'a>(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut sort: Vec<(&u32, &Vec<String>)> = self.0.iter().collect();
+    /// The lines in a `<g>` per layer, marked as layer for Inkscape. Every
+    /// layer gets a group, also when it is empty.
+    pub fn groups(&self, layers: &[String]) -> String {
+        let mut sort: Vec<(&Order, &Vec<String>)> = self.0.iter().collect();
+
+        sort.sort_by_key(|&(&order, _lines)| order);
 
-        sort.sort_by_key(|&(&prio, _lines)| prio);
+        let mut svg = String::new();
 
-        for (_, lines) in sort {
-            for line in lines {
-                writeln!(f, "{}", line)?;
+        for (group, name) in layers.iter().enumerate() {
+            svg += &format!(
+                "<g id=\"{}\" inkscape:groupmode=\"layer\" inkscape:label=\"{}\">\n",
+                name, name
+            );
+
+            for (_, lines) in sort.iter().filter(|(order, _)| order.group == group) {
+                for line in lines.iter() {
+                    svg += line;
+                    svg += "\n";
+                }
             }
+
+            svg += "</g>\n";
         }
 
-        Ok(())
+        svg
     }
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub struct SelectBox {
-    x: u32,
-    y: u32,
-    w: u32,
-    h: u32,
+    x: i64,
+    y: i64,
+    w: i64,
+    h: i64,
 }
 
 impl SelectBox {
-    pub fn new(x: u32, y: u32, w: u32, h: u32) -> SelectBox {
+    pub fn new(x: i64, y: i64, w: i64, h: i64) -> SelectBox {
         SelectBox { x, y, w, h }
     }
 
-    pub fn x_min(&self) -> u32 {
+    pub fn x_min(&self) -> i64 {
         self.x
     }
 
-    pub fn y_min(&self) -> u32 {
+    pub fn y_min(&self) -> i64 {
         self.y
     }
 
-    pub fn x_max(&self) -> u32 {
+    pub fn x_max(&self) -> i64 {
         self.x + self.w
     }
 
-    pub fn y_max(&self) -> u32 {
+    pub fn y_max(&self) -> i64 {
         self.y + self.h
     }
 
     /// width
-    pub fn w(&self) -> u32 {
+    pub fn w(&self) -> i64 {
         self.w
     }
 
     /// height
-    pub fn h(&self) -> u32 {
+    pub fn h(&self) -> i64 {
         self.h
     }
 
     /// return true if the coordinate is inside the box
-    pub fn is_inside(&self, x: u32, y: u32) -> bool {
+    pub fn is_inside(&self, x: i64, y: i64) -> bool {
         x > self.x_min() && x < self.x_max() && y > self.y_min() && y < self.y_max()
     }
+
+    /// the box grown with a margin on every side
+    pub fn rect(&self, margin: u32) -> Rect {
+        Rect {
+            x_min: self.x_min() as f64 - margin as f64,
+            y_min: self.y_min() as f64 - margin as f64,
+            x_max: self.x_max() as f64 + margin as f64,
+            y_max: self.y_max() as f64 + margin as f64,
+        }
+    }
+}
+
+/// Settings of the conversion
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Style file, the built in style when None
+    pub style_path: Option<String>,
+
+    /// Geometry is clipped this many metres outside the select box
+    pub margin: u32,
+
+    /// Simplify lines, drop nodes closer than this many metres to the line
+    pub tolerance: f64,
+
+    /// Projection of the select box and the svg coordinates
+    pub projection: Projection,
+
+    /// Number of decimals of the coordinates in the svg
+    pub precision: u32,
+
+    /// Where the nodes are kept, [NodeStore::Mmap] for large extracts
+    pub node_store: NodeStore,
+
+    /// Width and height of the symbols in metres, unless the style rule has
+    /// a size
+    pub symbol_size: f64,
+
+    /// Metres between the shields with the same ref
+    pub shield_spacing: f64,
+
+    /// Metres between the grid lines, 0 for no grid, the spacing of the
+    /// style when None
+    pub grid: Option<f64>,
+
+    /// Degrees between the lines of latitude and longitude, 0 for none, the
+    /// graticule of the style when None
+    pub graticule: Option<f64>,
+
+    /// Draw a scale bar, a north arrow and the title, date and attribution
+    pub furniture: bool,
+
+    /// Title in the box with the date and attribution
+    pub title: Option<String>,
+
+    /// Date under the title, as it is given
+    pub date: Option<String>,
+
+    /// The data source, OpenStreetMap by default
+    pub attribution: Option<String>,
+
+    /// A legend of the style rules drawn on the map
+    pub legend: bool,
+
+    /// Paper and scale, the svg is as large as the paper in millimetres
+    /// with the map inside the margins. See [Print::select_box] for the box
+    /// that fills it.
+    pub print: Option<Print>,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            style_path: None,
+            margin: 0,
+            tolerance: 0.5,
+            projection: Projection::default(),
+            precision: 1,
+            node_store: NodeStore::default(),
+            symbol_size: 14.0,
+            shield_spacing: 500.0,
+            grid: None,
+            graticule: None,
+            furniture: false,
+            title: None,
+            date: None,
+            attribution: Some("© OpenStreetMap contributors".to_string()),
+            legend: false,
+            print: None,
+        }
+    }
+}
+
+/// Metres on a millimetre of paper without a print scale, the svg has a
+/// pixel per metre and 96 pixels per inch
+const MM: f64 = 96.0 / 25.4;
+
+/// Text for in an xml element or attribute
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The tags for the desc of an element
+fn desc<'a>(tags: impl Iterator<Item = (&'a str, &'a str)>) -> String {
+    let tags: String = tags
+        .map(|(key, value)| format!("{}:{}; ", key, value))
+        .collect();
+
+    escape(&tags)
+}
+
+/// What the pass over the ways and nodes collects for a box
+struct Found {
+    svg: Svg,
+    pois: Pois,
+    texts: Texts,
+    shields: Shields,
+    /// the style rules that drew something
+    rules: BTreeSet<usize>,
+}
+
+impl Found {
+    fn new() -> Found {
+        Found {
+            svg: Svg::new(),
+            pois: Pois::new(),
+            texts: Texts::new(),
+            shields: Shields::new(),
+            rules: BTreeSet::new(),
+        }
+    }
+
+    fn combine(self, other: Found) -> Found {
+        Found {
+            svg: self.svg.combine(other.svg),
+            pois: self.pois.combine(other.pois),
+            texts: self.texts.combine(other.texts),
+            shields: self.shields.combine(other.shields),
+            rules: self.rules.union(&other.rules).copied().collect(),
+        }
+    }
+}
+
+/// The pass over the ways and nodes: the member ways of the multipolygons
+/// and what is found in the boxes, by the index of the box. Boxes without
+/// anything found are left out.
+struct Pass {
+    member_ways: MemberWays,
+    found: BTreeMap<usize, Found>,
+}
+
+impl Pass {
+    fn new() -> Pass {
+        Pass {
+            member_ways: MemberWays::new(),
+            found: BTreeMap::new(),
+        }
+    }
+
+    fn combine(self, other: Pass) -> Pass {
+        let mut found = self.found;
+
+        for (i, other) in other.found {
+            let combined = match found.remove(&i) {
+                Some(this) => this.combine(other),
+                None => other,
+            };
+            found.insert(i, combined);
+        }
+
+        Pass {
+            member_ways: self.member_ways.combine(other.member_ways),
+            found,
+        }
+    }
+}
+
+/// The boxes with the margin, and the rect around them all to pass over what
+/// is far from every box at once
+struct Clips {
+    rects: Vec<Rect>,
+    bounds: Rect,
+}
+
+impl Clips {
+    fn new(boxes: &[SelectBox], margin: u32) -> Result<Clips> {
+        let rects: Vec<Rect> = boxes
+            .iter()
+            .map(|select_box| select_box.rect(margin))
+            .collect();
+
+        let corners: Vec<geometry::Point> = rects
+            .iter()
+            .flat_map(|rect| [(rect.x_min, rect.y_min), (rect.x_max, rect.y_max)])
+            .collect();
+
+        let Some(bounds) = Rect::bounds(&corners) else {
+            bail!("No boxes to read");
+        };
+
+        Ok(Clips { rects, bounds })
+    }
+}
+
+/// ` data-key="highway" data-value="primary"` for the key the rule matched
+fn data(key: Option<&str>, tags: &[(&str, &str)]) -> String {
+    let Some(key) = key else {
+        return String::new();
+    };
+
+    match tags.iter().find(|&&(k, _)| k == key) {
+        Some((_, value)) => format!(
+            " data-key=\"{}\" data-value=\"{}\"",
+            escape(key),
+            escape(value)
+        ),
+        None => String::new(),
+    }
+}
+
+fn name<'a>(tags: &[(&str, &'a str)]) -> Option<&'a str> {
+    tags.iter()
+        .find(|&&(key, _)| key == "name")
+        .map(|&(_, value)| value)
+}
+
+/// A node in each of the boxes it is in, nothing when it is outside all of
+/// them
+fn node_pass(
+    id: i64,
+    (lat, lon): (f64, f64),
+    tags: Vec<(&str, &str)>,
+    style: &Style,
+    projection: &Projection,
+    clips: &Clips,
+    options: &Options,
+) -> Pass {
+    let mut pass = Pass::new();
+
+    let (x, y) = node_index::fixed(lat, lon, projection);
+    let point = node_index::point(x, y);
+
+    if !clips.bounds.contains(point) {
+        return pass;
+    }
+
+    for (i, _) in clips
+        .rects
+        .iter()
+        .enumerate()
+        .filter(|(_, clip)| clip.contains(point))
+    {
+        if let Some(found) = node(id, point, &tags, style, options) {
+            pass.found.insert(i, found);
+        }
+    }
+
+    pass
+}
+
+/// A node with a symbol or a label in the style, None without
+fn node(
+    id: i64,
+    point: geometry::Point,
+    tags: &[(&str, &str)],
+    style: &Style,
+    options: &Options,
+) -> Option<Found> {
+    let node_style = style.node(tags)?;
+
+    let mut found = Found {
+        rules: BTreeSet::from([node_style.rule]),
+        ..Found::new()
+    };
+    let mut symbol_size = 0.0;
+
+    if let Some(symbol) = node_style.symbol {
+        symbol_size = node_style.size.unwrap_or(options.symbol_size);
+        found.pois = Pois::one(Poi {
+            id,
+            point,
+            layer: node_style.layer,
+            z_index: node_style.z_index,
+            symbol: symbol.to_string(),
+            size: symbol_size,
+            class: node_style.class.to_string(),
+            data: data(node_style.key, tags),
+            tags: desc(tags.iter().copied()),
+        });
+    }
+
+    if let (Some(label), Some(name)) = (&node_style.label, name(tags)) {
+        found.texts = Texts::one(Text {
+            symbol: symbol_size / 2.0,
+            ..Text::new(format!("n{}", id), name, point, label)
+        });
+    }
+
+    Some(found)
+}
+
+/// The name inside an area, at the pole of inaccessibility of the part in
+/// the box. None when the area is too narrow for the text.
+fn area_text(id: String, name: &str, rings: &[Vec<geometry::Point>], label: &Label) -> Texts {
+    let rings: Vec<_> = rings.iter().filter(|ring| !ring.is_empty()).collect();
+
+    let Some((point, distance)) = geometry::pole_of_inaccessibility(&rings, 1.0) else {
+        return Texts::new();
+    };
+
+    let text = Text::new(id, name, point, label);
+    if 2.0 * distance < text.font_size {
+        return Texts::new();
+    }
+
+    Texts::one(Text {
+        area: rings
+            .iter()
+            .map(|ring| geometry::area(ring))
+            .fold(0.0, f64::max),
+        ..text
+    })
+}
+
+/// The OSM layer of a way, a bridge without a layer is on layer 1 and a
+/// tunnel on -1
+fn osm_layer(tags: &[(&str, &str)]) -> i32 {
+    let value = |key: &str| {
+        tags.iter()
+            .find(|&&(k, _)| k == key)
+            .map(|&(_, value)| value)
+    };
+
+    if let Some(layer) = value("layer").and_then(|layer| layer.trim().parse().ok()) {
+        return layer;
+    }
+
+    match (value("bridge"), value("tunnel")) {
+        (Some(bridge), _) if bridge != "no" => 1,
+        (_, Some(tunnel)) if tunnel != "no" => -1,
+        _ => 0,
+    }
+}
+
+/// Closed ways with a fill are areas, other ways are lines
+fn is_area(points: &[geometry::Point], style: &str) -> bool {
+    points.len() > 3 && points.first() == points.last() && style::is_filled(style)
+}
+
+/// The simplified way clipped to the box
+fn way_parts(
+    points: &[geometry::Point],
+    area: bool,
+    clip: &Rect,
+    options: &Options,
+) -> Vec<Vec<geometry::Point>> {
+    let simple = geometry::simplify(points, options.tolerance);
+
+    if area {
+        // small areas collapse when simplified
+        let ring = if simple.len() < 4 { points } else { &simple };
+        vec![geometry::clip_polygon(ring, clip)]
+    } else {
+        geometry::clip_line(&simple, clip)
+    }
+}
+
+/// A way with a style, clipped to the box
+fn styled_way(
+    id: i64,
+    points: &[geometry::Point],
+    way_tags: &[(&str, &str)],
+    way_style: &WayStyle,
+    clip: &Rect,
+    style: &Style,
+    options: &Options,
+) -> Found {
+    let mut found = Found::new();
+    let area = is_area(points, way_style.style);
+    let tags = desc(way_tags.iter().copied());
+
+    found.rules.insert(way_style.rule);
+
+    let parts = way_parts(points, area, clip, options);
+    let d = geometry::svg_path_d(&parts, options.precision);
+
+    // areas stay in the order of the z-index, lines follow the layer
+    let layer = if area { 0 } else { osm_layer(way_tags) };
+
+    found.svg = Svg::one(
+        Order::line(way_style.layer, way_style.z_index, layer, true),
+        format!(
+            "<path d=\"{}\" id=\"{}\" class=\"{}\"{}><desc>{}</desc></path>",
+            d,
+            id,
+            way_style.class,
+            data(way_style.key, way_tags),
+            tags
+        ),
+    );
+
+    if !area && way_style.casing.is_some() {
+        found.svg.append_line(
+            Order::line(way_style.layer, way_style.z_index, layer, false),
+            format!(
+                "<path d=\"{}\" id=\"{}-casing\" class=\"{}-casing\"/>",
+                d, id, way_style.class
+            ),
+        );
+    }
+
+    if let (Some(name), Some(label)) = (name(way_tags), &way_style.label) {
+        if area {
+            found.texts = area_text(id.to_string(), name, &parts, label);
+        } else if let Some(line) = label::way_label(id, name, &parts, label, options.precision) {
+            found
+                .svg
+                .append_line(Order::group(style.layer(LABELS)), line);
+        }
+    }
+
+    let reference = way_tags.iter().find(|&&(key, _)| key == "ref");
+    if let (false, Some(&(_, reference)), Some(class)) = (area, reference, way_style.shield) {
+        found.shields = Shields::along(id, class, reference, &parts, options.shield_spacing);
+    }
+
+    found
 }
 
 pub fn doit(
     select_box: SelectBox,
     input_path: String,
     output_path: String,
-    _style_path: Option<String>,
+    style_path: Option<String>,
+) -> Result<()> {
+    let options = Options {
+        style_path,
+        ..Options::default()
+    };
+
+    doit_with_options(select_box, input_path, output_path, &options)
+}
+
+pub fn doit_with_options(
+    select_box: SelectBox,
+    input_path: String,
+    output_path: String,
+    options: &Options,
 ) -> Result<()> {
-    let reader = ElementReader::from_path(&input_path)?;
+    let (source, mut found) = read(&input_path, &[select_box], options)?;
+
+    write(
+        &source,
+        found.remove(0),
+        select_box,
+        &output_path,
+        options,
+        |_, _, _| Vec::new(),
+    )
+}
+
+/// The area on sheets at the print scale and paper of the options, each
+/// `overlap` metres over its neighbours, and an index of the sheets. The
+/// pbf is read once for all sheets. The sheets are written next to the
+/// output with their number, `atlas.svg` becomes `atlas-1.svg` and so on
+/// and `atlas-index.svg`; the paths are returned.
+pub fn atlas(
+    area: SelectBox,
+    overlap: i64,
+    input_path: String,
+    output_path: String,
+    options: &Options,
+) -> Result<Vec<String>> {
+    let Some(print) = options.print else {
+        bail!("An atlas needs a print scale and paper");
+    };
+
+    let paper = print.select_box(0, 0)?;
+    let sheets = atlas::sheets(&area, paper.w(), paper.h(), overlap)?;
+    let (index_box, index_print) = atlas::index(&sheets, print)?;
+
+    let mut boxes: Vec<SelectBox> = sheets.iter().map(|sheet| sheet.select_box).collect();
+    boxes.push(index_box);
+
+    let (source, found) = read(&input_path, &boxes, options)?;
+    let mut found = found.into_iter();
+    let outside = print.margin >= 5.0;
+
+    let mut paths = Vec::new();
+
+    for (sheet, found) in sheets.iter().zip(found.by_ref()) {
+        let path = atlas::path(&output_path, &atlas::suffix(sheet.number, sheets.len()));
+
+        write(
+            &source,
+            found,
+            sheet.select_box,
+            &path,
+            options,
+            |rect, mm, taken| atlas::references(sheet, rect, mm, outside, taken, options.precision),
+        )?;
+
+        paths.push(path);
+    }
+
+    let path = atlas::path(&output_path, "index");
+    let index_options = Options {
+        print: Some(index_print),
+        ..options.clone()
+    };
+
+    write(
+        &source,
+        found.next().unwrap_or_else(Found::new),
+        index_box,
+        &path,
+        &index_options,
+        |_, mm, taken| atlas::frames(&sheets, mm, taken, options.precision),
+    )?;
 
-    let node_index =
-        reader.par_map_reduce(NodeIndex::convert, NodeIndex::new, NodeIndex::combine)?;
+    paths.push(path);
 
-    let node_index_select = node_index.filter(&select_box);
+    Ok(paths)
+}
 
-    let reader = ElementReader::from_path(&input_path)?;
+/// What is read from the pbf once for all maps
+struct Source {
+    style: Style,
+    projection: Projection,
+    node_index: Nodes,
+    multipolygons: MultiPolygons,
+    member_ways: MemberWays,
+}
 
-    let style = Style::new();
+/// Read the pbf for the maps of the boxes, with what is found in each box
+fn read(input_path: &str, boxes: &[SelectBox], options: &Options) -> Result<(Source, Vec<Found>)> {
+    if !options.shield_spacing.is_finite() || options.shield_spacing <= 0.0 {
+        bail!(
+            "Invalid shield spacing of {} m, use more than 0",
+            options.shield_spacing
+        );
+    }
+
+    let style = match &options.style_path {
+        Some(path) => Style::from_file(path)?,
+        None => Style::new(),
+    };
+
+    let projection = options.projection.resolve(input_path)?;
+
+    let node_index = Nodes::load(options.node_store, input_path, &projection)?;
+
+    let clips = Clips::new(boxes, options.margin)?;
+
+    let reader = ElementReader::from_path(input_path)?;
+
+    let multipolygons = reader.par_map_reduce(
+        MultiPolygons::convert,
+        MultiPolygons::new,
+        MultiPolygons::combine,
+    )?;
+
+    let member_ways = multipolygons.member_ways();
+
+    let reader = ElementReader::from_path(input_path)?;
 
     // Count the ways
-    let mut svg = reader.par_map_reduce(
+    let Pass {
+        member_ways,
+        mut found,
+    } = reader.par_map_reduce(
         |element| match element {
             Element::Way(way) => {
-                if way.refs().any(|id| node_index_select.contains_key(id)) {
-                    let mut tags: String = way
-                        .tags()
-                        .map(|(key, value)| format!("{}:{}; ", key, value))
-                        .collect();
-
-                    tags = tags.replace('&', "&amp;");
-
-                    let mut svg = Svg::new();
-
-                    for (key, value) in way.tags() {
-                        if let Some((prio, style)) = style.get(key, value) {
-                            svg = Svg::one(
-                                prio,
-                                format!(
-                                    "<path d=\"{}\" id=\"{}\" style=\"{}\"><desc>{}</desc></path>",
-                                    node_index.svg_path_d(way.refs()),
-                                    way.id(),
-                                    style,
-                                    tags
-                                ),
-                            )
-                        }
+                let mut pass = Pass::new();
+
+                if member_ways.contains(&way.id()) {
+                    pass.member_ways = MemberWays::one(way.id(), way.refs().collect());
+                }
+
+                let points = node_index.points(way.refs());
+
+                // no need to style or clip a way far from all boxes
+                let Some(bounds) = Rect::bounds(&points) else {
+                    return pass;
+                };
+                if !clips.bounds.touches(&bounds) {
+                    return pass;
+                }
+
+                let way_tags: Vec<(&str, &str)> = way.tags().collect();
+                let way_style = style.way(&way_tags);
+                let area = way_style
+                    .as_ref()
+                    .is_some_and(|way_style| is_area(&points, way_style.style));
+
+                for (i, clip) in clips.rects.iter().enumerate() {
+                    let inside = clip.touches(&bounds)
+                        && if area {
+                            geometry::area_intersects(&[&points], clip)
+                        } else {
+                            geometry::line_intersects(&points, clip)
+                        };
+
+                    if !inside {
+                        continue;
                     }
 
-                    if svg.0.is_empty() {
+                    let Some(way_style) = &way_style else {
                         debug!(
                             "Missing id:{} {} nodes:{}",
                             way.id(),
-                            tags,
+                            desc(way.tags()),
                             way.refs().len()
                         );
-                    }
+                        continue;
+                    };
 
-                    svg
-                } else {
-                    Svg::new()
+                    pass.found.insert(
+                        i,
+                        styled_way(
+                            way.id(),
+                            &points,
+                            &way_tags,
+                            way_style,
+                            clip,
+                            &style,
+                            options,
+                        ),
+                    );
                 }
+
+                pass
             }
 
-            Element::Node(_n) => Svg::new(),
-            Element::DenseNode(_n) => Svg::new(),
+            Element::Node(n) => node_pass(
+                n.id(),
+                (n.lat(), n.lon()),
+                n.tags().collect(),
+                &style,
+                &projection,
+                &clips,
+                options,
+            ),
+            Element::DenseNode(n) => node_pass(
+                n.id(),
+                (n.lat(), n.lon()),
+                n.tags().collect(),
+                &style,
+                &projection,
+                &clips,
+                options,
+            ),
 
-            _ => Svg::new(),
+            _ => Pass::new(),
         },
-        Svg::new,
-        Svg::combine,
+        Pass::new,
+        Pass::combine,
     )?;
 
-    // grid
+    let found = (0..boxes.len())
+        .map(|i| found.remove(&i).unwrap_or_else(Found::new))
+        .collect();
+
+    Ok((
+        Source {
+            style,
+            projection,
+            node_index,
+            multipolygons,
+            member_ways,
+        },
+        found,
+    ))
+}
+
+/// Write the map of the box with what was found in it. `extra` draws in the
+/// furniture layer on the rect of the box with the metres per millimetre,
+/// before the grid and the labels take their room.
+fn write(
+    source: &Source,
+    found: Found,
+    select_box: SelectBox,
+    output_path: &str,
+    options: &Options,
+    extra: impl FnOnce(&Rect, f64, &mut Vec<Rect>) -> Vec<String>,
+) -> Result<()> {
+    let Source {
+        style,
+        projection,
+        node_index,
+        multipolygons,
+        member_ways,
+    } = source;
+
+    let Found {
+        mut svg,
+        pois,
+        mut texts,
+        shields,
+        mut rules,
+    } = found;
+
+    let clip = select_box.rect(options.margin);
+
+    // multipolygons
+
+    for mp in multipolygons.iter() {
+        let rings: Vec<_> = member_ways
+            .rings(mp)
+            .iter()
+            .map(|ring| node_index.points(ring.iter().copied()))
+            .collect();
+
+        if !geometry::area_intersects(&rings, &clip) {
+            continue;
+        }
+
+        let tags = desc(mp.tags.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+
+        let rings: Vec<_> = rings
+            .iter()
+            .map(|ring| {
+                let simple = geometry::simplify(ring, options.tolerance);
+                let ring = if simple.len() < 4 { ring } else { &simple };
+                geometry::clip_polygon(ring, &clip)
+            })
+            .collect();
+
+        let d = geometry::svg_path_d(&rings, options.precision);
+
+        let mp_tags: Vec<(&str, &str)> = mp
+            .tags
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+
+        let Some(way_style) = style.way(&mp_tags) else {
+            debug!(
+                "Missing relation id:{} {} rings:{}",
+                mp.id,
+                tags,
+                rings.len()
+            );
+            continue;
+        };
+
+        rules.insert(way_style.rule);
+
+        if let (Some(name), Some(label)) = (name(&mp_tags), &way_style.label) {
+            texts = texts.combine(area_text(format!("r{}", mp.id), name, &rings, label));
+        }
 
-    for x in (select_box.x_min()..select_box.x_max()).step_by(1000) {
         svg.append_line(
-            1000,
+            Order::line(way_style.layer, way_style.z_index, 0, true),
             format!(
-                r#"<line x1="{}" y1="-{}" x2="{}" y2="-{}" stroke="black" />"#,
-                x,
-                select_box.y_min(),
-                x,
-                select_box.y_max()
+                "<path d=\"{}\" id=\"r{}\" class=\"{}\"{} fill-rule=\"evenodd\"><desc>{}</desc></path>",
+                d,
+                mp.id,
+                way_style.class,
+                data(way_style.key, &mp_tags),
+                tags
             ),
         );
     }
 
-    for y in (select_box.y_min()..select_box.y_max()).step_by(1000) {
+    // points of interest
+
+    for poi in pois.iter() {
+        svg.append_line(
+            Order::line(poi.layer, poi.z_index, 0, true),
+            poi.svg(options.precision),
+        );
+    }
+
+    let mut taken = pois.boxes();
+
+    // scale bar, north arrow and title
+
+    let mm = options.print.map_or(MM, |print| print.metres_per_mm());
+
+    if options.print.is_some() {
         svg.append_line(
-            1000,
+            Order::group(style.layer(FURNITURE)),
+            furniture::neatline(&select_box.rect(0), options.precision),
+        );
+    }
+
+    for line in extra(&select_box.rect(0), mm, &mut taken) {
+        svg.append_line(Order::group(style.layer(FURNITURE)), line);
+    }
+
+    if options.furniture {
+        let group = Order::group(style.layer(FURNITURE));
+        let rect = select_box.rect(0);
+        let precision = options.precision;
+
+        let lines = [
+            furniture::scale_bar(&rect, mm, &mut taken, precision),
+            furniture::north_arrow(&rect, mm, projection, &mut taken, precision),
+            furniture::cartouche(
+                &rect,
+                mm,
+                [
+                    options.title.as_deref(),
+                    options.date.as_deref(),
+                    options.attribution.as_deref(),
+                ],
+                &mut taken,
+                precision,
+            ),
+        ];
+
+        for line in lines.into_iter().flatten() {
+            svg.append_line(group, line);
+        }
+    }
+
+    if options.legend {
+        for line in legend::legend(
+            &select_box.rect(0),
+            mm,
+            &style.legend(&rules),
+            &mut taken,
+            options.precision,
+        ) {
+            svg.append_line(Order::group(style.layer(FURNITURE)), line);
+        }
+    }
+
+    // grid and graticule, their labels first at the edges
+
+    let grid_group = Order::group(style.layer(GRID));
+
+    let spacing = options.grid.unwrap_or(style.grid.spacing);
+    if spacing > 0.0 {
+        for line in grid::grid(
+            &select_box.rect(0),
+            spacing,
+            &style.grid.label,
+            &mut taken,
+            options.precision,
+        )? {
+            svg.append_line(grid_group, line);
+        }
+    }
+
+    let step = options.graticule.unwrap_or(style.grid.graticule);
+    if step > 0.0 {
+        for line in grid::graticule(
+            &select_box.rect(0),
+            step,
+            projection,
+            &style.grid.graticule_label,
+            &mut taken,
+            options.precision,
+        )? {
+            svg.append_line(grid_group, line);
+        }
+    }
+
+    // shields and labels, without overlap
+
+    let (lines, mut symbols) = shields.place(
+        style,
+        &mut taken,
+        &select_box.rect(0),
+        options.shield_spacing,
+        options.precision,
+    )?;
+
+    for line in lines {
+        svg.append_line(Order::group(style.layer(LABELS)), line);
+    }
+
+    for line in texts.place(&mut taken, &select_box.rect(0), options.precision) {
+        svg.append_line(Order::group(style.layer(LABELS)), line);
+    }
+
+    symbols.extend(pois.iter().map(|poi| poi.symbol.clone()));
+    let symbols = symbol::defs(&symbols, style)?;
+
+    // the paper in millimetres around the map, or a pixel per metre
+    let (width, height, view_box) = match options.print {
+        Some(print) => {
+            let (width, height) = print.page();
+            let margin = print.margin * mm;
+            (
+                format!("{}mm", width),
+                format!("{}mm", height),
+                format!(
+                    "{} {} {} {}",
+                    geometry::round(select_box.x_min() as f64 - margin, options.precision),
+                    geometry::round(-select_box.y_max() as f64 - margin, options.precision),
+                    geometry::round(width * mm, options.precision),
+                    geometry::round(height * mm, options.precision)
+                ),
+            )
+        }
+        None => (
+            select_box.w.to_string(),
+            select_box.h.to_string(),
             format!(
-                r#"<line x1="{}" y1="-{}" x2="{}" y2="-{}" stroke="black" />"#,
+                "{} {} {} {}",
                 select_box.x_min(),
-                y,
-                select_box.x_max(),
-                y
+                -select_box.y_max(),
+                select_box.w,
+                select_box.h
             ),
-        );
-    }
+        ),
+    };
 
-    // consuming to much resources
-    //   svg = svg.combine(Svg::one(
-    //       1,
-    //       r##"<defs>
-    //   <pattern id="star" viewBox="0,0,10,10" width="10%" height="10%">
-    //     <polygon points="0,0 2,5 0,10 5,8 10,10 8,5 10,0 5,2"/>
-    //   </pattern>
-    //   <pattern id="parking" patternUnits="userSpaceOnUse" width="10" height="6">
-    //     <rect width="10" height="6" fill="white"/>
-    //     <rect x="0" y="0" width="9" height="5" fill="LightSkyBlue"/>
-    //   </pattern>
-    // </defs>"##
-    //           .to_string(),
-    //   ));
-
-    let mut output = std::fs::File::create(output_path)?;
-
-    write!(
-        output,
+    write_svg(
+        output_path,
+        (&width, &height, &view_box),
+        &[
+            pattern::defs(&style.patterns(&rules), style)?,
+            symbols,
+            style.css(mm),
+            svg.groups(style.layers()),
+        ],
+    )
+}
+
+/// The svg file with the parts, the defs, the css and the drawing, or a pdf
+/// of it when the output ends in `.pdf`
+fn write_svg(
+    output_path: &str,
+    (width, height, view_box): (&str, &str, &str),
+    parts: &[String],
+) -> Result<()> {
+    let document = format!(
         "<svg
   width=\"{}\" 
   height=\"{}\" 
-  viewBox=\"{} -{} {} {}\" 
+  viewBox=\"{}\" 
   xmlns=\"http://www.w3.org/2000/svg\" 
   xmlns:xlink=\"http://www.w3.org/1999/xlink\" 
+  xmlns:inkscape=\"http://www.inkscape.org/namespaces/inkscape\" 
 >
 {}
-{} 
 </svg>\n",
-        select_box.w,
-        select_box.h,
-        select_box.x_min(),
-        select_box.y_max(),
-        select_box.w,
-        select_box.h,
-        style::defs_pattern(),
-        svg
-    )?;
-    Ok(())
+        width,
+        height,
+        view_box,
+        parts.join("\n")
+    );
+
+    if pdf::is_pdf(output_path) {
+        pdf::write(&document, output_path)
+    } else {
+        Ok(std::fs::write(output_path, document)?)
+    }
+}
+
+/// A preview of the style of the options: every rule in a grid of cells
+/// with a sample, the caption and the tags, to review the style or compare
+/// two versions
+pub fn preview(output_path: String, options: &Options) -> Result<()> {
+    let style = match &options.style_path {
+        Some(path) => Style::from_file(path)?,
+        None => Style::new(),
+    };
+
+    let entries = style.entries();
+    let rules: BTreeSet<usize> = (0..entries.len()).collect();
+    let (lines, width, height) = preview::preview(&entries, options.precision);
+
+    write_svg(
+        &output_path,
+        (
+            &width.to_string(),
+            &height.to_string(),
+            &format!("0 0 {} {}", width, height),
+        ),
+        &[
+            pattern::defs(&style.patterns(&rules), &style)?,
+            symbol::defs(&style.symbols(), &style)?,
+            style.css(MM),
+            lines.join("\n"),
+        ],
+    )
 }
@@ -1,46 +1,201 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::Parser;
 use log::*;
-use osm_bpf_to_svg::SelectBox;
+use osm_bpf_to_svg::{NodeStore, Options, Orientation, Paper, Print, Projection, Scale, SelectBox};
 
 /// Simple program to greet a person
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
     /// Name of input file
-    input: String,
+    #[arg(required_unless_present = "preview")]
+    input: Option<String>,
 
-    /// Name of the prson to greet
-    x: u32,
+    /// x (east) of the lower left corner, in metres of the projection
+    #[arg(allow_negative_numbers = true, required_unless_present = "preview")]
+    x: Option<i64>,
 
-    /// Name of the person to greet
-    y: u32,
+    /// y (north) of the lower left corner, in metres of the projection
+    #[arg(allow_negative_numbers = true, required_unless_present = "preview")]
+    y: Option<i64>,
 
-    /// Name of the person to greet
-    #[arg(default_value_t = 1000)]
-    w: u32,
+    /// width in metres, 1000 by default, from the paper with --scale, of
+    /// the whole area with --atlas
+    w: Option<i64>,
 
-    /// height
-    #[arg(default_value_t = 1000)]
-    h: u32,
+    /// height in metres, 1000 by default, from the paper with --scale, of
+    /// the whole area with --atlas
+    h: Option<i64>,
 
-    /// Name of output
+    /// Name of output, a pdf when it ends in .pdf
     #[arg(short, long, default_value = "out.svg")]
     output: String,
 
-    /// Name of style file
+    /// Style file, see style/default.toml for the format
     #[arg(short, long)]
     style: Option<String>,
+
+    /// Keep this many metres of geometry outside the box
+    #[arg(short, long, default_value_t = Options::default().margin)]
+    margin: u32,
+
+    /// Simplify lines, drop nodes closer than this many metres to the line
+    #[arg(short, long, default_value_t = Options::default().tolerance)]
+    tolerance: f64,
+
+    /// Projection: rd, webmercator, utm (zone of the data) or utm31, utm32n, ...
+    #[arg(short, long, default_value_t = Projection::Rd)]
+    projection: Projection,
+
+    /// Number of decimals of the coordinates in the svg
+    #[arg(long, default_value_t = Options::default().precision)]
+    precision: u32,
+
+    /// Node store: memory, sorted or mmap (a temporary file, for large extracts)
+    #[arg(short, long, default_value_t = NodeStore::Memory)]
+    node_store: NodeStore,
+
+    /// Width and height of the symbols in metres, unless the style sets a size
+    #[arg(long, default_value_t = Options::default().symbol_size)]
+    symbol_size: f64,
+
+    /// Metres between the road shields with the same ref
+    #[arg(long, default_value_t = Options::default().shield_spacing)]
+    shield_spacing: f64,
+
+    /// Metres between the grid lines, 0 for no grid, default from the style
+    #[arg(long)]
+    grid: Option<f64>,
+
+    /// Degrees between the lines of latitude and longitude, 0 for none,
+    /// default from the style
+    #[arg(long)]
+    graticule: Option<f64>,
+
+    /// Draw a scale bar, a north arrow and the title, date and attribution
+    #[arg(long)]
+    furniture: bool,
+
+    /// Title of the map, with --furniture
+    #[arg(long)]
+    title: Option<String>,
+
+    /// Date on the map, with --furniture
+    #[arg(long)]
+    date: Option<String>,
+
+    /// Attribution on the map, with --furniture
+    #[arg(long, default_value = "© OpenStreetMap contributors")]
+    attribution: String,
+
+    /// Draw a legend of the style rules on the map
+    #[arg(long)]
+    legend: bool,
+
+    /// Print at this scale, like 1:10000: the width and height of the map
+    /// follow from the paper
+    #[arg(long)]
+    scale: Option<Scale>,
+
+    /// Paper with --scale: a0 to a5, letter, legal, tabloid or 300x400 mm
+    #[arg(long, default_value_t = Paper::A4)]
+    paper: Paper,
+
+    /// Orientation of the paper with --scale: portrait or landscape
+    #[arg(long, default_value_t = Orientation::Portrait)]
+    orientation: Orientation,
+
+    /// Millimetres of white paper around the map with --scale
+    #[arg(long, default_value_t = 10.0)]
+    paper_margin: f64,
+
+    /// Cover the area with numbered sheets of the paper at the scale, and an
+    /// index. The output name gets the sheet number: out-1.svg, out-index.svg
+    #[arg(long, requires = "scale")]
+    atlas: bool,
+
+    /// Metres the sheets of the atlas overlap
+    #[arg(long, default_value_t = 100)]
+    overlap: i64,
+
+    /// Draw every rule of the style in a grid of samples instead of a map,
+    /// without input file and box
+    #[arg(long, conflicts_with_all = ["input", "atlas"])]
+    preview: bool,
 }
 
-impl Args {}
+impl Args {
+    fn options(&self) -> Options {
+        Options {
+            style_path: self.style.clone(),
+            margin: self.margin,
+            tolerance: self.tolerance,
+            projection: self.projection,
+            precision: self.precision,
+            node_store: self.node_store,
+            symbol_size: self.symbol_size,
+            shield_spacing: self.shield_spacing,
+            grid: self.grid,
+            graticule: self.graticule,
+            furniture: self.furniture,
+            title: self.title.clone(),
+            date: self.date.clone(),
+            attribution: Some(self.attribution.clone()),
+            legend: self.legend,
+            print: self.scale.map(|scale| Print {
+                scale,
+                paper: self.paper,
+                orientation: self.orientation,
+                margin: self.paper_margin,
+            }),
+        }
+    }
+
+    fn select_box(&self, options: &Options) -> Result<SelectBox> {
+        let (Some(x), Some(y)) = (self.x, self.y) else {
+            bail!("The x and y of the map are missing");
+        };
+
+        match options.print {
+            Some(_) if !self.atlas && (self.w.is_some() || self.h.is_some()) => {
+                bail!("The width and height follow from --scale, leave them out")
+            }
+            Some(print) if !self.atlas => print.select_box(x, y),
+            _ => Ok(SelectBox::new(
+                x,
+                y,
+                self.w.unwrap_or(1000),
+                self.h.unwrap_or(1000),
+            )),
+        }
+    }
+}
 
 fn main() -> Result<()> {
     env_logger::init();
     let args = Args::parse();
     debug!("args {:?}", args);
-    let select_box = SelectBox::new(args.x, args.y, args.w, args.h);
-    osm_bpf_to_svg::doit(select_box, args.input, args.output, args.style)?;
+    let options = args.options();
+
+    if args.preview {
+        return osm_bpf_to_svg::preview(args.output, &options);
+    }
+
+    let select_box = args.select_box(&options)?;
+    let Some(input) = args.input else {
+        bail!("The input file is missing");
+    };
+
+    if args.atlas {
+        let paths = osm_bpf_to_svg::atlas(select_box, args.overlap, input, args.output, &options)?;
+        info!(
+            "{} sheets and the index: {}",
+            paths.len() - 1,
+            paths.join(" ")
+        );
+    } else {
+        osm_bpf_to_svg::doit_with_options(select_box, input, args.output, &options)?;
+    }
 
     Ok(())
 }
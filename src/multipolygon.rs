@@ -0,0 +1,125 @@
+use log::debug;
+use osmpbf::{Element, RelMemberType};
+use std::collections::{HashMap, HashSet};
+
+/// A relation with `type=multipolygon`, only the way members are kept
+pub struct MultiPolygon {
+    pub id: i64,
+    pub tags: Vec<(String, String)>,
+    pub ways: Vec<i64>,
+}
+
+pub struct MultiPolygons(Vec<MultiPolygon>);
+
+impl MultiPolygons {
+    pub fn new() -> MultiPolygons {
+        MultiPolygons(Vec::new())
+    }
+
+    pub fn combine(mut self, other: MultiPolygons) -> MultiPolygons {
+        self.0.extend(other.0);
+        self
+    }
+
+    pub fn convert(e: Element) -> MultiPolygons {
+        match e {
+            Element::Relation(relation) => {
+                if !relation
+                    .tags()
+                    .any(|(key, value)| key == "type" && value == "multipolygon")
+                {
+                    return MultiPolygons::new();
+                }
+
+                let ways = relation
+                    .members()
+                    .filter(|member| member.member_type == RelMemberType::Way)
+                    .filter(|member| matches!(member.role(), Ok("outer" | "inner" | "")))
+                    .map(|member| member.member_id)
+                    .collect();
+
+                MultiPolygons(vec![MultiPolygon {
+                    id: relation.id(),
+                    tags: relation
+                        .tags()
+                        .filter(|&(key, _)| key != "type")
+                        .map(|(key, value)| (key.to_string(), value.to_string()))
+                        .collect(),
+                    ways,
+                }])
+            }
+            _ => MultiPolygons::new(),
+        }
+    }
+
+    /// Id's of all the ways that are needed to build the multipolygons
+    pub fn member_ways(&self) -> HashSet<i64> {
+        self.0
+            .iter()
+            .flat_map(|mp| mp.ways.iter().copied())
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &MultiPolygon> {
+        self.0.iter()
+    }
+}
+
+/// The node refs of the member ways of the multipolygons
+pub struct MemberWays(HashMap<i64, Vec<i64>>);
+
+impl MemberWays {
+    pub fn new() -> MemberWays {
+        MemberWays(HashMap::new())
+    }
+
+    pub fn one(id: i64, refs: Vec<i64>) -> MemberWays {
+        MemberWays(HashMap::from([(id, refs)]))
+    }
+
+    pub fn combine(mut self, other: MemberWays) -> MemberWays {
+        self.0.extend(other.0);
+        self
+    }
+
+    /// Join the member ways of a multipolygon to closed rings.
+    ///
+    /// Because the rings are drawn with `fill-rule:evenodd` there is no need to
+    /// know which ring is outer and which is inner.
+    pub fn rings(&self, mp: &MultiPolygon) -> Vec<Vec<i64>> {
+        let mut rings = Vec::new();
+        let mut open: Vec<Vec<i64>> = Vec::new();
+
+        for id in &mp.ways {
+            match self.0.get(id) {
+                Some(refs) if refs.len() < 2 => {}
+                Some(refs) if refs.first() == refs.last() => rings.push(refs.clone()),
+                Some(refs) => open.push(refs.clone()),
+                None => debug!("Missing way:{} of relation:{}", id, mp.id),
+            }
+        }
+
+        while let Some(mut ring) = open.pop() {
+            loop {
+                if ring.first() == ring.last() {
+                    rings.push(ring);
+                    break;
+                }
+
+                let end = *ring.last().unwrap();
+                if let Some(i) = open.iter().position(|w| w.first() == Some(&end)) {
+                    let way = open.swap_remove(i);
+                    ring.extend_from_slice(&way[1..]);
+                } else if let Some(i) = open.iter().position(|w| w.last() == Some(&end)) {
+                    let way = open.swap_remove(i);
+                    ring.extend(way.iter().rev().skip(1));
+                } else {
+                    debug!("Unclosed ring in relation:{}", mp.id);
+                    break;
+                }
+            }
+        }
+
+        rings
+    }
+}
@@ -1,63 +1,59 @@
-use crate::SelectBox;
-use osmpbf::{Element, WayRefIter};
-use rijksdriehoek::wgs84_to_rijksdriehoek;
+use crate::geometry::Point;
+use crate::Projection;
+use osmpbf::Element;
 use std::collections::HashMap;
 
-pub struct NodeIndex(HashMap<i64, (u32, u32)>);
+/// Node coordinates are stored in centimetres, an i32 holds ±21000 km which
+/// is enough for every projection.
+const SCALE: f64 = 100.0;
+
+pub struct NodeIndex(HashMap<i64, (i32, i32)>);
 
 impl NodeIndex {
     pub fn new() -> NodeIndex {
         NodeIndex(HashMap::new())
     }
 
-    pub fn one(id: i64, x: u32, y: u32) -> NodeIndex {
+    pub fn one(id: i64, x: i32, y: i32) -> NodeIndex {
         NodeIndex(HashMap::from([(id, (x, y))]))
     }
 
+    fn from_wgs84(id: i64, lat: f64, lon: f64, projection: &Projection) -> NodeIndex {
+        let (x, y) = fixed(lat, lon, projection);
+
+        NodeIndex::one(id, x, y)
+    }
+
     pub fn combine(mut self, ni: NodeIndex) -> NodeIndex {
         self.0.extend(ni.0);
         self
     }
 
-    pub fn filter(&self, select_box: &SelectBox) -> NodeIndex {
-        let mut r = NodeIndex::new();
-
-        for (&id, &(x, y)) in &self.0 {
-            if select_box.is_inside(x, y) {
-                r.0.insert(id, (x, y));
-            }
-        }
-
-        r
+    /// coordinates of the nodes, unknown nodes are skipped
+    pub fn points(&self, line: impl IntoIterator<Item = i64>) -> Vec<Point> {
+        line.into_iter()
+            .filter_map(|r| self.0.get(&r))
+            .map(|&(x, y)| point(x, y))
+            .collect()
     }
 
-    /// svg path definition
-    pub fn svg_path_d(&self, line: WayRefIter) -> String {
-        let mut d = String::new();
-        for (i, r) in line.enumerate() {
-            let (x, y) = self.0.get(&r).copied().unwrap_or_default();
-            if i == 0 {
-                d += &format!("M {} -{} ", x, y);
-            } else {
-                d += &format!("L {} -{} ", x, y);
-            }
-        }
-
-        d
-    }
-
-    pub fn convert(e: Element) -> NodeIndex {
+    pub fn convert(e: Element, projection: &Projection) -> NodeIndex {
         match e {
-            Element::DenseNode(n) => {
-                let (x, y) = wgs84_to_rijksdriehoek(n.lat(), n.lon());
-
-                NodeIndex::one(n.id, x as u32, y as u32)
-            }
+            Element::Node(n) => NodeIndex::from_wgs84(n.id(), n.lat(), n.lon(), projection),
+            Element::DenseNode(n) => NodeIndex::from_wgs84(n.id(), n.lat(), n.lon(), projection),
             _ => NodeIndex::new(),
         }
     }
+}
 
-    pub fn contains_key(&self, key: i64) -> bool {
-        self.0.contains_key(&key)
-    }
+/// projected coordinates in centimetres
+pub fn fixed(lat: f64, lon: f64, projection: &Projection) -> (i32, i32) {
+    let (x, y) = projection.project(lat, lon);
+
+    ((x * SCALE).round() as i32, (y * SCALE).round() as i32)
+}
+
+/// centimetres back to metres
+pub fn point(x: i32, y: i32) -> Point {
+    (x as f64 / SCALE, y as f64 / SCALE)
 }
@@ -0,0 +1,180 @@
+use crate::geometry::Point;
+use crate::node_index::{self, NodeIndex};
+use crate::Projection;
+use anyhow::{bail, Context, Result};
+use memmap2::{Mmap, MmapMut};
+use osmpbf::{Element, ElementReader};
+use std::fmt::{Display, Formatter};
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
+
+/// Where the projected nodes are kept until the ways are read
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum NodeStore {
+    /// Hash map in memory, the fastest for small extracts
+    #[default]
+    Memory,
+    /// Array sorted by node id in memory, less than half of the hash map
+    Sorted,
+    /// Array sorted by node id in a memory mapped temporary file, the
+    /// operating system keeps only the pages in use in memory
+    Mmap,
+}
+
+impl FromStr for NodeStore {
+    type Err = anyhow::Error;
+
+    /// `memory`, `sorted` or `mmap`
+    fn from_str(s: &str) -> Result<NodeStore> {
+        match s.to_lowercase().as_str() {
+            "memory" => Ok(NodeStore::Memory),
+            "sorted" => Ok(NodeStore::Sorted),
+            "mmap" => Ok(NodeStore::Mmap),
+            _ => bail!("Unknown node store {}, use memory, sorted or mmap", s),
+        }
+    }
+}
+
+impl Display for NodeStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeStore::Memory => write!(f, "memory"),
+            NodeStore::Sorted => write!(f, "sorted"),
+            NodeStore::Mmap => write!(f, "mmap"),
+        }
+    }
+}
+
+/// A node with the coordinates in centimetres, the layout of the node file
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct Record {
+    id: i64,
+    x: i32,
+    y: i32,
+}
+
+impl Record {
+    fn convert(e: Element, projection: &Projection) -> Option<Record> {
+        let (id, lat, lon) = match e {
+            Element::Node(n) => (n.id(), n.lat(), n.lon()),
+            Element::DenseNode(n) => (n.id(), n.lat(), n.lon()),
+            _ => return None,
+        };
+        let (x, y) = node_index::fixed(lat, lon, projection);
+
+        Some(Record { id, x, y })
+    }
+
+    fn bytes(&self) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&self.id.to_ne_bytes());
+        bytes[8..12].copy_from_slice(&self.x.to_ne_bytes());
+        bytes[12..].copy_from_slice(&self.y.to_ne_bytes());
+        bytes
+    }
+}
+
+/// The nodes of the input file in one of the [NodeStore]s
+pub enum Nodes {
+    Memory(NodeIndex),
+    Sorted(Vec<Record>),
+    Mmap(Option<Mmap>),
+}
+
+impl Nodes {
+    /// Read all nodes of the file
+    pub fn load(store: NodeStore, input_path: &str, projection: &Projection) -> Result<Nodes> {
+        let reader = ElementReader::from_path(input_path)?;
+
+        match store {
+            NodeStore::Memory => Ok(Nodes::Memory(reader.par_map_reduce(
+                |element| NodeIndex::convert(element, projection),
+                NodeIndex::new,
+                NodeIndex::combine,
+            )?)),
+
+            NodeStore::Sorted => {
+                let mut records = reader.par_map_reduce(
+                    |element| Vec::from_iter(Record::convert(element, projection)),
+                    Vec::new,
+                    |mut a, b| {
+                        a.extend(b);
+                        a
+                    },
+                )?;
+                records.sort_unstable_by_key(|r| r.id);
+                Ok(Nodes::Sorted(records))
+            }
+
+            NodeStore::Mmap => Ok(Nodes::Mmap(node_file(reader, projection)?)),
+        }
+    }
+
+    /// coordinates of the nodes, unknown nodes are skipped
+    pub fn points(&self, line: impl IntoIterator<Item = i64>) -> Vec<Point> {
+        let records = match self {
+            Nodes::Memory(index) => return index.points(line),
+            Nodes::Sorted(records) => records,
+            Nodes::Mmap(Some(mmap)) => records(mmap),
+            Nodes::Mmap(None) => &[][..],
+        };
+
+        line.into_iter()
+            .filter_map(|id| records.binary_search_by_key(&id, |r| r.id).ok())
+            .map(|i| node_index::point(records[i].x, records[i].y))
+            .collect()
+    }
+}
+
+/// Write the nodes one by one to a temporary file and map it, the file is
+/// sorted in place when the input is not sorted by id. None without nodes,
+/// an empty file can not be mapped.
+fn node_file<R: std::io::Read + Send>(
+    reader: ElementReader<R>,
+    projection: &Projection,
+) -> Result<Option<Mmap>> {
+    let file = tempfile::tempfile().context("Can not create the node file")?;
+
+    let mut out = BufWriter::new(&file);
+    let mut written = Ok(());
+    let mut sorted = true;
+    let mut last = i64::MIN;
+
+    reader.for_each(|element| {
+        if let Some(record) = Record::convert(element, projection) {
+            sorted &= record.id > last;
+            last = record.id;
+            if written.is_ok() {
+                written = out.write_all(&record.bytes());
+            }
+        }
+    })?;
+
+    written.context("Can not write the node file")?;
+    out.flush().context("Can not write the node file")?;
+    drop(out);
+
+    if file.metadata()?.len() == 0 {
+        return Ok(None);
+    }
+
+    // Safety: the file is private to this process, nobody else changes it
+    let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+
+    if !sorted {
+        let len = mmap.len() / std::mem::size_of::<Record>();
+        // Safety: the mapping is page aligned and holds whole records
+        let records =
+            unsafe { std::slice::from_raw_parts_mut(mmap.as_mut_ptr() as *mut Record, len) };
+        records.sort_unstable_by_key(|r| r.id);
+    }
+
+    Ok(Some(mmap.make_read_only()?))
+}
+
+fn records(mmap: &Mmap) -> &[Record] {
+    let len = mmap.len() / std::mem::size_of::<Record>();
+    // Safety: the mapping is page aligned and holds whole records
+    unsafe { std::slice::from_raw_parts(mmap.as_ptr() as *const Record, len) }
+}
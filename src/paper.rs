@@ -0,0 +1,180 @@
+use crate::SelectBox;
+use anyhow::{bail, Context, Result};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Paper size in millimetres, upright
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Paper {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Paper {
+    pub const A4: Paper = Paper {
+        width: 210.0,
+        height: 297.0,
+    };
+}
+
+impl FromStr for Paper {
+    type Err = anyhow::Error;
+
+    /// `a0` to `a5`, `letter`, `legal`, `tabloid` or width by height in
+    /// millimetres like `300x400`
+    fn from_str(s: &str) -> Result<Paper> {
+        let (width, height) = match s.to_lowercase().as_str() {
+            "a0" => (841.0, 1189.0),
+            "a1" => (594.0, 841.0),
+            "a2" => (420.0, 594.0),
+            "a3" => (297.0, 420.0),
+            "a4" => (210.0, 297.0),
+            "a5" => (148.0, 210.0),
+            "letter" => (215.9, 279.4),
+            "legal" => (215.9, 355.6),
+            "tabloid" => (279.4, 431.8),
+            size => {
+                let Some((width, height)) = size.split_once('x') else {
+                    bail!(
+                        "Unknown paper {}, use a0 to a5, letter, legal, tabloid or 300x400",
+                        s
+                    );
+                };
+                let mm = |v: &str| -> Result<f64> {
+                    v.trim()
+                        .trim_end_matches("mm")
+                        .parse()
+                        .with_context(|| format!("Invalid paper size {}", s))
+                };
+                (mm(width)?, mm(height)?)
+            }
+        };
+
+        if !width.is_finite() || !height.is_finite() || width <= 0.0 || height <= 0.0 {
+            bail!("Invalid paper size {}", s);
+        }
+
+        Ok(Paper { width, height })
+    }
+}
+
+impl Display for Paper {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum Orientation {
+    #[default]
+    Portrait,
+    Landscape,
+}
+
+impl FromStr for Orientation {
+    type Err = anyhow::Error;
+
+    /// `portrait` or `landscape`
+    fn from_str(s: &str) -> Result<Orientation> {
+        match s.to_lowercase().as_str() {
+            "portrait" => Ok(Orientation::Portrait),
+            "landscape" => Ok(Orientation::Landscape),
+            _ => bail!("Unknown orientation {}, use portrait or landscape", s),
+        }
+    }
+}
+
+impl Display for Orientation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Orientation::Portrait => write!(f, "portrait"),
+            Orientation::Landscape => write!(f, "landscape"),
+        }
+    }
+}
+
+/// The denominator of a map scale, `1:10000` is 10000
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Scale(pub f64);
+
+impl FromStr for Scale {
+    type Err = anyhow::Error;
+
+    /// `1:10000`, `1:10 000` or `10000`
+    fn from_str(s: &str) -> Result<Scale> {
+        let number = s.trim().strip_prefix("1:").unwrap_or(s.trim());
+        let scale: f64 = number
+            .replace([' ', '_', ','], "")
+            .parse()
+            .with_context(|| format!("Invalid scale {}, use 1:10000", s))?;
+
+        if !scale.is_finite() || scale <= 0.0 {
+            bail!("Invalid scale {}, use 1:10000", s);
+        }
+
+        Ok(Scale(scale))
+    }
+}
+
+impl Display for Scale {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "1:{}", self.0)
+    }
+}
+
+/// A map printed at a scale on a sheet of paper, the map inside the margins
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Print {
+    pub scale: Scale,
+    pub paper: Paper,
+    pub orientation: Orientation,
+    /// white paper around the map in millimetres
+    pub margin: f64,
+}
+
+impl Print {
+    /// Width and height of the paper in millimetres, turned
+    pub fn page(&self) -> (f64, f64) {
+        let Paper { width, height } = self.paper;
+
+        match self.orientation {
+            Orientation::Portrait => (width.min(height), width.max(height)),
+            Orientation::Landscape => (width.max(height), width.min(height)),
+        }
+    }
+
+    /// Metres on a millimetre of paper
+    pub fn metres_per_mm(&self) -> f64 {
+        self.scale.0 / 1000.0
+    }
+
+    /// The box that fills the paper inside the margins at the scale, with
+    /// the lower left corner at x, y
+    pub fn select_box(&self, x: i64, y: i64) -> Result<SelectBox> {
+        if !self.scale.0.is_finite() || self.scale.0 <= 0.0 {
+            bail!("Invalid scale {}, use 1:10000", self.scale);
+        }
+        if !self.margin.is_finite() || self.margin < 0.0 {
+            bail!("Invalid paper margin of {} mm, use 0 or more", self.margin);
+        }
+
+        let Paper { width, height } = self.paper;
+        if !width.is_finite() || !height.is_finite() || width <= 0.0 || height <= 0.0 {
+            bail!("Invalid paper size {}", self.paper);
+        }
+
+        let (width, height) = self.page();
+        let (width, height) = (width - 2.0 * self.margin, height - 2.0 * self.margin);
+
+        if width <= 0.0 || height <= 0.0 {
+            bail!("The margins do not leave room for the map on the paper");
+        }
+
+        Ok(SelectBox::new(
+            x,
+            y,
+            (width * self.metres_per_mm()).round() as i64,
+            (height * self.metres_per_mm()).round() as i64,
+        ))
+    }
+}
@@ -0,0 +1,29 @@
+use crate::style::Style;
+use anyhow::Result;
+use std::collections::BTreeSet;
+
+/// `<defs>` with a `<pattern>` for every pattern of `style/pattern` the
+/// styles refer to as `fill:url(#name)`, tiled in metres
+pub fn defs(names: &BTreeSet<String>, style: &Style) -> Result<String> {
+    if names.is_empty() {
+        return Ok(String::new());
+    }
+
+    let mut defs = "<defs>\n".to_string();
+
+    for name in names {
+        let file = style.svg_file("pattern", name)?;
+
+        // the ids in the pattern must be unique in the map
+        defs += &format!(
+            "<pattern id=\"{}\" patternUnits=\"userSpaceOnUse\" width=\"{}\" height=\"{}\" viewBox=\"{}\">\n{}\n</pattern>\n",
+            name,
+            file.width,
+            file.height,
+            file.view_box,
+            file.content_with_ids(&format!("pattern-{}", name))
+        );
+    }
+
+    Ok(defs + "</defs>")
+}
@@ -0,0 +1,56 @@
+use anyhow::{bail, Context, Result};
+use svg2pdf::usvg::{self, fontdb, PostProcessingSteps, TreeParsing, TreePostProc};
+
+/// Output files ending in `.pdf` are written as pdf, the others as svg
+pub fn is_pdf(output_path: &str) -> bool {
+    output_path.to_lowercase().ends_with(".pdf")
+}
+
+/// The svg document as a pdf page of the same size: the millimetres of a
+/// print scale, or else the pixels at 96 per inch. Text is drawn with the
+/// fonts of the system, the generic sans-serif is DejaVu Sans when there is
+/// no Arial. Without a sans-serif font the text would be left out, that is
+/// an error.
+pub fn write(svg: &str, output_path: &str) -> Result<()> {
+    let mut fonts = fontdb::Database::new();
+    fonts.load_system_fonts();
+
+    if fonts.is_empty() {
+        bail!("No fonts found for the text of the pdf, install a font like DejaVu Sans");
+    }
+
+    if !has_family(&fonts, "Arial") && has_family(&fonts, "DejaVu Sans") {
+        fonts.set_sans_serif_family("DejaVu Sans");
+    }
+    if !has_family(&fonts, "Courier New") && has_family(&fonts, "DejaVu Sans Mono") {
+        fonts.set_monospace_family("DejaVu Sans Mono");
+    }
+
+    let sans_serif = fontdb::Query {
+        families: &[fontdb::Family::SansSerif],
+        ..fontdb::Query::default()
+    };
+    if fonts.query(&sans_serif).is_none() {
+        bail!("No sans-serif font for the text of the pdf, install Arial or DejaVu Sans");
+    }
+
+    let mut tree =
+        usvg::Tree::from_str(svg, &usvg::Options::default()).context("Invalid svg for the pdf")?;
+    tree.postprocess(PostProcessingSteps::default(), &fonts);
+
+    let pdf = svg2pdf::convert_tree(
+        &tree,
+        svg2pdf::Options {
+            dpi: 96.0,
+            ..svg2pdf::Options::default()
+        },
+    );
+
+    std::fs::write(output_path, pdf).with_context(|| format!("Cannot write {}", output_path))
+}
+
+fn has_family(fonts: &fontdb::Database, family: &str) -> bool {
+    fonts
+        .faces()
+        .any(|face| face.families.iter().any(|(name, _)| name == family))
+}
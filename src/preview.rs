@@ -0,0 +1,88 @@
+use crate::escape;
+use crate::geometry;
+use crate::label::text_width;
+use crate::legend::swatch;
+use crate::style::LegendEntry;
+
+/// Cells in a row of the preview
+const COLUMNS: usize = 6;
+
+/// Metres of the sample in a cell, the rules are drawn as large as on a map
+const SAMPLE: (f64, f64) = (80.0, 40.0);
+
+/// Metres around a cell
+const PADDING: f64 = 10.0;
+
+const CAPTION: f64 = 10.0;
+const TAGS: f64 = 8.0;
+
+/// The css of the background, the captions and the tags
+pub fn css() -> String {
+    format!(
+        ".preview-background {{ fill:white }}
+.preview-caption {{ font-family:sans-serif;font-size:{}px }}
+.preview-tags {{ font-family:monospace;font-size:{}px;fill:#555 }}
+",
+        CAPTION, TAGS
+    )
+}
+
+/// Every rule of the style in a grid of cells, row by row in the order of
+/// the style: a sample of the line, area, symbol or label, the caption and
+/// the tags. Returns the svg lines with the width and height.
+pub fn preview(entries: &[LegendEntry], precision: u32) -> (Vec<String>, f64, f64) {
+    let round = |v: f64| geometry::round(v, precision);
+
+    let cell_width = entries
+        .iter()
+        .flat_map(|entry| {
+            [
+                text_width(&entry.caption, CAPTION),
+                text_width(&entry.tags, TAGS),
+            ]
+        })
+        .fold(SAMPLE.0, f64::max)
+        + 2.0 * PADDING;
+    let cell_height = SAMPLE.1 + 1.5 * (CAPTION + TAGS) + 2.0 * PADDING;
+
+    let columns = entries.len().clamp(1, COLUMNS);
+    let rows = entries.len().div_ceil(COLUMNS).max(1);
+    let (width, height) = (
+        (columns as f64 * cell_width).ceil(),
+        (rows as f64 * cell_height).ceil(),
+    );
+
+    let mut lines = vec![format!(
+        "<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" class=\"preview-background\"/>",
+        round(width),
+        round(height)
+    )];
+
+    for (i, entry) in entries.iter().enumerate() {
+        let x = (i % COLUMNS) as f64 * cell_width + PADDING;
+        let y = (i / COLUMNS) as f64 * cell_height + PADDING;
+
+        lines.push(format!("<g class=\"preview-{}\">", entry.class));
+
+        // the swatch has y up, like the map
+        lines.extend(swatch(entry, (x, -y - SAMPLE.1 / 2.0), SAMPLE, precision));
+
+        let caption_y = y + SAMPLE.1 + 1.5 * CAPTION;
+        lines.push(format!(
+            "<text x=\"{}\" y=\"{}\" class=\"preview-caption\">{}</text>",
+            round(x),
+            round(caption_y),
+            escape(&entry.caption)
+        ));
+        lines.push(format!(
+            "<text x=\"{}\" y=\"{}\" class=\"preview-tags\">{}</text>",
+            round(x),
+            round(caption_y + 1.5 * TAGS),
+            escape(&entry.tags)
+        ));
+
+        lines.push("</g>".to_string());
+    }
+
+    (lines, width, height)
+}
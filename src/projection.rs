@@ -0,0 +1,200 @@
+use anyhow::{bail, Context, Result};
+use osmpbf::{BlobDecode, BlobReader, Element};
+use rijksdriehoek::{rijksdriehoek_to_wgs84, wgs84_to_rijksdriehoek};
+use std::f64::consts::PI;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// WGS84 ellipsoid
+const A: f64 = 6_378_137.0;
+const F: f64 = 1.0 / 298.257_223_563;
+
+/// Map projection from WGS84 latitude and longitude to metres
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum Projection {
+    /// Rijksdriehoekscoördinaten, the Dutch national grid
+    #[default]
+    Rd,
+    /// Web Mercator as used by the online maps (EPSG:3857)
+    WebMercator,
+    /// Universal Transverse Mercator zone 1..=60
+    Utm { zone: u8, south: bool },
+    /// UTM in the zone of the first node of the input file
+    UtmAuto,
+}
+
+impl Projection {
+    /// Replace [Projection::UtmAuto] by the zone of the first node in the file
+    pub fn resolve(self, input_path: &str) -> Result<Projection> {
+        if self != Projection::UtmAuto {
+            return Ok(self);
+        }
+
+        let (lat, lon) = first_node(input_path)?;
+        let zone = (((lon + 180.0) / 6.0).floor() as i32).clamp(0, 59) as u8 + 1;
+
+        Ok(Projection::Utm {
+            zone,
+            south: lat < 0.0,
+        })
+    }
+
+    /// x (east) and y (north) in metres
+    pub fn project(&self, lat: f64, lon: f64) -> (f64, f64) {
+        match *self {
+            Projection::Rd => wgs84_to_rijksdriehoek(lat, lon),
+            Projection::WebMercator => {
+                let lat = lat.clamp(-85.051_128_78, 85.051_128_78).to_radians();
+                (A * lon.to_radians(), A * (PI / 4.0 + lat / 2.0).tan().ln())
+            }
+            Projection::Utm { zone, south } => utm(lat, lon, zone, south),
+            Projection::UtmAuto => unreachable!("resolve the utm zone first"),
+        }
+    }
+
+    /// Latitude and longitude of x (east) and y (north) in metres
+    pub fn unproject(&self, x: f64, y: f64) -> (f64, f64) {
+        match *self {
+            Projection::Rd => rijksdriehoek_to_wgs84(x, y),
+            Projection::WebMercator => (
+                (2.0 * (y / A).exp().atan() - PI / 2.0).to_degrees(),
+                (x / A).to_degrees(),
+            ),
+            Projection::Utm { zone, south } => {
+                let lon0 = (zone as f64 - 1.0) * 6.0 - 180.0 + 3.0;
+                let north = if south { y - 10_000_000.0 } else { y };
+                let lat = north / 110_574.0;
+                let lon = lon0 + (x - 500_000.0) / (111_320.0 * lat.to_radians().cos());
+
+                newton(self, x, y, (lat, lon))
+            }
+            Projection::UtmAuto => unreachable!("resolve the utm zone first"),
+        }
+    }
+}
+
+/// The inverse of the projection by Newton's method from a first guess,
+/// with the derivatives taken numerically
+fn newton(projection: &Projection, x: f64, y: f64, (mut lat, mut lon): (f64, f64)) -> (f64, f64) {
+    let d = 1e-6;
+
+    for _ in 0..10 {
+        let (px, py) = projection.project(lat, lon);
+        let (dx, dy) = (x - px, y - py);
+        if dx.abs() < 1e-4 && dy.abs() < 1e-4 {
+            break;
+        }
+
+        let (x_lat, y_lat) = projection.project(lat + d, lon);
+        let (x_lon, y_lon) = projection.project(lat, lon + d);
+        let (a, b) = ((x_lat - px) / d, (x_lon - px) / d);
+        let (c, e) = ((y_lat - py) / d, (y_lon - py) / d);
+        let det = a * e - b * c;
+
+        lat += (e * dx - b * dy) / det;
+        lon += (a * dy - c * dx) / det;
+    }
+
+    (lat, lon)
+}
+
+/// Transverse Mercator, the series of Snyder "Map Projections: A Working Manual"
+fn utm(lat: f64, lon: f64, zone: u8, south: bool) -> (f64, f64) {
+    let k0 = 0.9996;
+    let e2 = F * (2.0 - F);
+    let ep2 = e2 / (1.0 - e2);
+
+    let lon0 = (zone as f64 - 1.0) * 6.0 - 180.0 + 3.0;
+    let phi = lat.to_radians();
+
+    let n = A / (1.0 - e2 * phi.sin().powi(2)).sqrt();
+    let t = phi.tan().powi(2);
+    let c = ep2 * phi.cos().powi(2);
+    let a = phi.cos() * (lon - lon0).to_radians();
+
+    let m = A
+        * ((1.0 - e2 / 4.0 - 3.0 * e2.powi(2) / 64.0 - 5.0 * e2.powi(3) / 256.0) * phi
+            - (3.0 * e2 / 8.0 + 3.0 * e2.powi(2) / 32.0 + 45.0 * e2.powi(3) / 1024.0)
+                * (2.0 * phi).sin()
+            + (15.0 * e2.powi(2) / 256.0 + 45.0 * e2.powi(3) / 1024.0) * (4.0 * phi).sin()
+            - (35.0 * e2.powi(3) / 3072.0) * (6.0 * phi).sin());
+
+    let x = k0
+        * n
+        * (a + (1.0 - t + c) * a.powi(3) / 6.0
+            + (5.0 - 18.0 * t + t * t + 72.0 * c - 58.0 * ep2) * a.powi(5) / 120.0)
+        + 500_000.0;
+
+    let y = k0
+        * (m + n
+            * phi.tan()
+            * (a * a / 2.0
+                + (5.0 - t + 9.0 * c + 4.0 * c * c) * a.powi(4) / 24.0
+                + (61.0 - 58.0 * t + t * t + 600.0 * c - 330.0 * ep2) * a.powi(6) / 720.0));
+
+    (x, if south { y + 10_000_000.0 } else { y })
+}
+
+fn first_node(input_path: &str) -> Result<(f64, f64)> {
+    for blob in BlobReader::from_path(input_path)? {
+        if let BlobDecode::OsmData(block) = blob?.decode()? {
+            for element in block.elements() {
+                match element {
+                    Element::Node(n) => return Ok((n.lat(), n.lon())),
+                    Element::DenseNode(n) => return Ok((n.lat(), n.lon())),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    bail!("No nodes in {} to choose the utm zone", input_path)
+}
+
+impl FromStr for Projection {
+    type Err = anyhow::Error;
+
+    /// `rd`, `webmercator`, `utm` or `utm31`, `utm31n`, `utm23s`
+    fn from_str(s: &str) -> Result<Projection> {
+        let s = s.to_lowercase();
+
+        match s.as_str() {
+            "rd" => return Ok(Projection::Rd),
+            "webmercator" | "mercator" => return Ok(Projection::WebMercator),
+            "utm" => return Ok(Projection::UtmAuto),
+            _ => {}
+        }
+
+        if let Some(zone) = s.strip_prefix("utm") {
+            let (zone, south) = match zone.strip_suffix('s') {
+                Some(zone) => (zone, true),
+                None => (zone.strip_suffix('n').unwrap_or(zone), false),
+            };
+
+            let zone: u8 = zone
+                .parse()
+                .with_context(|| format!("Invalid utm zone in {}", s))?;
+
+            if !(1..=60).contains(&zone) {
+                bail!("Utm zone {} is not between 1 and 60", zone);
+            }
+
+            return Ok(Projection::Utm { zone, south });
+        }
+
+        bail!("Unknown projection {}, use rd, webmercator or utm", s)
+    }
+}
+
+impl Display for Projection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Projection::Rd => write!(f, "rd"),
+            Projection::WebMercator => write!(f, "webmercator"),
+            Projection::Utm { zone, south } => {
+                write!(f, "utm{}{}", zone, if *south { "s" } else { "n" })
+            }
+            Projection::UtmAuto => write!(f, "utm"),
+        }
+    }
+}
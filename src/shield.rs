@@ -0,0 +1,198 @@
+use crate::escape;
+use crate::geometry::{self, Point, Rect};
+use crate::style::Style;
+use crate::symbol::symbol_id;
+use anyhow::Result;
+use log::debug;
+use std::collections::{BTreeSet, HashMap};
+
+/// The shields have room for 1 to 11 letters on 1 to 4 lines
+const MAX_LETTERS: usize = 11;
+const MAX_LINES: usize = 4;
+
+/// The style of the `.shield-text` class of the refs on the shields
+pub const TEXT_STYLE: &str = "font-family:sans-serif;font-size:10px;font-weight:bold;fill:#222";
+const LINE_HEIGHT: f64 = 12.0;
+
+/// The symbol of a shield with its width and height
+type Symbol = (String, (f64, f64));
+
+/// The ref of a road in a shield of `symbols/shields`
+#[derive(Debug, Clone)]
+pub struct Shield {
+    pub way_id: i64,
+    /// road class, the first part of the file name like `motorway`
+    pub class: String,
+    /// a line for every ref, `A12;E35` gets two lines
+    pub lines: Vec<String>,
+    pub point: Point,
+}
+
+impl Shield {
+    /// The longest line and the number of lines, the size of the shield
+    fn size(&self) -> (usize, usize) {
+        let letters = self.lines.iter().map(|line| line.chars().count()).max();
+
+        (letters.unwrap_or(1).max(1), self.lines.len())
+    }
+
+    fn svg(&self, symbol: &str, n: usize, (width, height): (f64, f64), precision: u32) -> String {
+        let round = |v: f64| geometry::round(v, precision);
+        let (x, y) = self.point;
+
+        let mut svg = format!(
+            "<use xlink:href=\"#{}\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" id=\"shield-{}-{}\"/>",
+            symbol_id(symbol),
+            round(x - width / 2.0),
+            round(-y - height / 2.0),
+            width,
+            height,
+            self.way_id,
+            n
+        );
+
+        let first = (self.lines.len() as f64 - 1.0) / 2.0;
+        for (i, line) in self.lines.iter().enumerate() {
+            svg += &format!(
+                "<text x=\"{}\" y=\"{}\" class=\"shield-text\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+                round(x),
+                round(-y + (i as f64 - first) * LINE_HEIGHT),
+                escape(line)
+            );
+        }
+
+        svg
+    }
+}
+
+pub struct Shields(Vec<Shield>);
+
+impl Shields {
+    pub fn new() -> Shields {
+        Shields(Vec::new())
+    }
+
+    pub fn combine(mut self, other: Shields) -> Shields {
+        self.0.extend(other.0);
+        self
+    }
+
+    /// Shields along the parts of a road, in the middle of every part and
+    /// every `spacing` metres on a longer part. Refs too long for the largest
+    /// shield get none.
+    pub fn along(
+        way_id: i64,
+        class: &str,
+        reference: &str,
+        parts: &[Vec<Point>],
+        spacing: f64,
+    ) -> Shields {
+        let lines: Vec<String> = reference
+            .split(';')
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect();
+
+        if lines.is_empty()
+            || lines.len() > MAX_LINES
+            || lines.iter().any(|line| line.chars().count() > MAX_LETTERS)
+        {
+            debug!("No shield for way {} ref {}", way_id, reference);
+            return Shields::new();
+        }
+
+        let mut shields = Shields::new();
+
+        for part in parts {
+            let length = geometry::length(part);
+            let count = (length / spacing).floor().max(1.0) as usize;
+
+            for i in 0..count {
+                let distance = (i as f64 + 0.5) * length / count as f64;
+                if let Some(point) = geometry::point_at(part, distance) {
+                    shields.0.push(Shield {
+                        way_id,
+                        class: class.to_string(),
+                        lines: lines.clone(),
+                        point,
+                    });
+                }
+            }
+        }
+
+        shields
+    }
+
+    /// Place the shields in the box without overlap. A shield closer than
+    /// `spacing` to a shield with the same ref, as on the other carriageway
+    /// or the next piece of the road, is left out. Returns the svg and the
+    /// symbols used.
+    pub fn place(
+        mut self,
+        style: &Style,
+        taken: &mut Vec<Rect>,
+        clip: &Rect,
+        spacing: f64,
+        precision: u32,
+    ) -> Result<(Vec<String>, BTreeSet<String>)> {
+        self.0.sort_by(|a, b| {
+            a.way_id
+                .cmp(&b.way_id)
+                .then(a.point.0.total_cmp(&b.point.0))
+                .then(a.point.1.total_cmp(&b.point.1))
+        });
+
+        // the symbol and its size for the class and size of a shield, None
+        // when the style has no shield large enough
+        let mut symbols: HashMap<(&str, (usize, usize)), Option<Symbol>> = HashMap::new();
+        let mut placed: Vec<(&Shield, String)> = Vec::new();
+        let mut lines = Vec::new();
+
+        for shield in &self.0 {
+            let (letters, rows) = shield.size();
+            let key = (shield.class.as_str(), (letters, rows));
+            let symbol = match symbols.get(&key) {
+                Some(symbol) => symbol.clone(),
+                None => {
+                    let symbol = match style.shield(&shield.class, letters, rows) {
+                        Some(name) => {
+                            let file = style.svg_file("symbols", &name)?;
+                            Some((name, (file.width, file.height)))
+                        }
+                        None => None,
+                    };
+                    symbols.insert(key, symbol.clone());
+                    symbol
+                }
+            };
+
+            let Some((symbol, size)) = symbol else {
+                debug!(
+                    "No {} shield for {} letters on {} lines of way {}",
+                    shield.class, letters, rows, shield.way_id
+                );
+                continue;
+            };
+
+            let rect = Rect::around(shield.point, size.0, size.1);
+
+            let repeated = placed.iter().any(|(other, _)| {
+                other.lines == shield.lines
+                    && (other.point.0 - shield.point.0).hypot(other.point.1 - shield.point.1)
+                        < spacing
+            });
+
+            if repeated || !clip.covers(&rect) || taken.iter().any(|t| t.intersects(&rect)) {
+                continue;
+            }
+
+            lines.push(shield.svg(&symbol, placed.len(), size, precision));
+            taken.push(rect);
+            placed.push((shield, symbol));
+        }
+
+        let symbols = placed.into_iter().map(|(_, symbol)| symbol).collect();
+
+        Ok((lines, symbols))
+    }
+}
@@ -1,165 +1,850 @@
-use log::debug;
-use std::collections::HashMap;
-
-
-type PrioStyleLine<'a> = (u32, &'a str);
-
-pub struct Style<'a>(HashMap<&'a str, (HashMap<&'a str, PrioStyleLine<'a>>, PrioStyleLine<'a>)>);
-
-impl Style<'_> {
-    pub fn new() -> Style<'static> {
-        Style(HashMap::from([
-            (
-                "highway",
-                (
-                    HashMap::from([
-                        ("path", (100, "stroke:#002a5a;fill:none")),
-                        ("residential", (100, "stroke-width:3;stroke:black;fill:none")),
-                        ("primary", (100, "stroke-width:6;stroke:black;fill:none")),
-                        ("secondary", (100, "stroke-width:4.5;stroke:black;fill:none")),
-                        ("tertiary", (100, "stroke-width:3;stroke:black;fill:none")),
-                        ("motorway", (100, "stroke-width:9;stroke:red;fill:none")),
-                        (
-                            "motorway_link",
-                            (100, "stroke-width:4.5;stroke:red;fill:none"),
-                        ),
-                        ("footway", (100, "stroke:#002a5a;fill:none")),
-                        ("track", (100, "stroke:#002a5a;fill:none")),
-                        ("service", (100, "stroke:#002a5a;fill:none")),
-                        ("cycleway", (100, "stroke:#002a5a;fill:none")),
-                        ("unclassified", (100, "stroke:#002a5a;fill:none")),
-                    ]),
-                    (11, "stroke:#030038;fill:none"),
-                ),
-            ),
-            (
-                "building",
-                (
-                    HashMap::from([
-                        ("house", (20, "stroke:blue; fill:purple")),
-                        ("yes", (20, "stroke:blue; fill:#ffd62e")),
-                        ("shed", (20, "stroke:blue; fill:#ffd62e")),
-                        ("apartments", (20, "stroke:blue; fill:#ffd62e")),
-                        ("church", (20, "stroke:blue; fill:#fb6bff")),
-                        ("school", (20, "stroke:blue; fill:#fb6bff")),
-                        ("commercial", (20, "stroke:blue; fill:purple")),
-                        ("retail", (20, "stroke:blue; fill:purple")),
-                        ("construction", (20, "stroke:blue; fill:none")),
-                    ]),
-                    (21, "stroke:blue; fill:#ffd020"),
-                ),
-            ),
-            (
-                "landuse",
-                (
-                    HashMap::from([
-                        ("forest", (5, "stroke:#009e07; fill:#169400")),
-                        ("grass", (5, "stroke:#009e07; fill:#6bff88")),
-                        ("residential", (5, "stroke:#009e07; fill:#e2ff16")),
-                        ("education", (5, "stroke:#009e07; fill:#007f5f")),
-                        ("farmland", (5, "stroke:#009e07; fill:#CD853F")),
-                        ("meadow", (5, "stroke:#009e07; fill:url(#leaftype_broadleaved)"))
-                    ]),
-                    (2, "stroke:#009e07; fill:#007f5f"),
-                ),
-            ),
-            (
-                "natural",
-                (
-                    HashMap::from([
-                        ("shrubbery", (50, "stroke:none; fill:green")),
-                        ("tree_row", (50, "stroke:green; fill:none")),
-                        ("water", (50, "stroke:Aqua; fill:RoyalBlue")),
-                    ]),
-                    (50, "stroke:#009e07; fill:#007f5f"),
-                ),
-            ),
-            (
-                "barrier",
-                (
-                    HashMap::from([
-                        ("fence", (50, "stroke:red; fill:none")),
-                        ("wall", (50, "stroke:darkkhaki; fill:none")),
-                        ("hedge", (50, "stroke:green; fill:none")),
-                    ]),
-                    (20, "stroke:red; fill:none"),
-                ),
-            ),
-            (
-                "leisure",
-                (
-                    HashMap::from([
-                        (
-                            "playground",
-                            (50, "stroke:palegoldenrod; fill:palegoldenrod"),
-                        ),
-                        ("dog_park", (50, "stroke:brown; fill:yellowgreen")),
-                        ("garden", (50, "stroke:greenyellow; fill:greenyellow")),
-                        ("pitch", (50, "stroke:chocolate; fill:chocolate")),
-                        ("swimming_pool", (50, "stroke:blue; fill:dodgerblue")),
-                    ]),
-                    (20, "stroke:brown; fill:none"),
-                ),
-            ),
-            (
-                "railway",
-                (
-                    HashMap::from([(
-                        "narrow_gauge",
-                        (50, "stroke:black;fill:none;stroke-width:2;stroke-miterlimit:4;stroke-dasharray:20, 20;stroke-dashoffset:0"),
-
-                    ),
-                        ("rail", (50, "stroke:black;fill:none;stroke-width:4;stroke-miterlimit:4;stroke-dasharray:10, 10;stroke-dashoffset:0")),
-                        ("platform", (50, "stroke:Gray; fill:DarkGray")),
-                    ]),
-                    (20, "stroke:brown; fill:none"),
-                ),
-            ),
-            (
-                "amenity",
-                (
-                    HashMap::from([(
-                        "parking",
-                        (9, "stroke:LightSkyBlue; fill:url(#parking)"),
-
-                    ),
-                    ]),
-                    (9, "stroke:LightSkyBlue; fill:LightSkyBlue"),
-                ),
-            ),
-        ]))
-    }
-
-    pub fn get(&self, key: &str, value: &str) -> Option<(u32, &str)> {
-        if let Some((map, default)) = self.0.get(key) {
-            if let Some(&s) = map.get(value) {
-                Some(s)
-            } else {
-                debug!("use default for {}:{}", key, value);
-                Some(*default)
-            }
+use crate::svg_file::SvgFile;
+use crate::{atlas, furniture, label, legend, preview, shield};
+use anyhow::{anyhow, bail, Context, Result};
+use include_dir::{include_dir, Dir};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use toml::Spanned;
+
+/// The symbols and patterns of the built in style, in the binary so they
+/// are found wherever it is installed
+static BUILT_IN: Dir = include_dir!("$CARGO_MANIFEST_DIR/style");
+
+/// The layer of the rules of a style without layers
+const DEFAULT_LAYER: &str = "map";
+
+/// The layer of the grid lines
+pub const GRID: &str = "grid";
+
+const GRID_LABEL: &str =
+    "font-family:sans-serif;font-size:12px;stroke:white;stroke-width:3;paint-order:stroke";
+
+/// The layer of the labels and shields
+pub const LABELS: &str = "labels";
+
+/// The layer of the scale bar, north arrow and title
+pub const FURNITURE: &str = "furniture";
+
+/// A style file, see `style/default.toml` for the format
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StyleFile {
+    layers: Option<Vec<String>>,
+    #[serde(default)]
+    grid: GridStyle,
+    rule: Vec<Spanned<RuleFile>>,
+}
+
+/// The `[grid]` of a style file: the lines of the projection and of
+/// latitude and longitude, with their coordinates at the edges of the map
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case", default)]
+pub struct GridStyle {
+    /// metres between the lines, 0 for no grid
+    pub spacing: f64,
+    pub style: String,
+    /// text style of the kilometres at the edges
+    pub label: String,
+    /// degrees between the lines of latitude and longitude, 0 for none
+    pub graticule: f64,
+    pub graticule_style: String,
+    /// text style of the degrees at the edges
+    pub graticule_label: String,
+}
+
+impl Default for GridStyle {
+    fn default() -> GridStyle {
+        GridStyle {
+            spacing: 1000.0,
+            style: "stroke:black;fill:none".to_string(),
+            label: GRID_LABEL.to_string(),
+            graticule: 0.0,
+            graticule_style: "stroke:#1f4ea8;stroke-dasharray:8 4;fill:none".to_string(),
+            graticule_label: format!("{};fill:#1f4ea8", GRID_LABEL),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFile {
+    key: Option<String>,
+    value: Option<String>,
+    tags: Option<Vec<String>>,
+    #[serde(rename = "z-index", alias = "prio")]
+    z_index: u32,
+    layer: Option<String>,
+    #[serde(default)]
+    style: String,
+    symbol: Option<String>,
+    size: Option<f64>,
+    label: Option<String>,
+    halo: Option<String>,
+    #[serde(default)]
+    importance: u32,
+    shield: Option<String>,
+    casing: Option<String>,
+    legend: Option<String>,
+}
+
+/// A test on the tags of a way or relation
+#[derive(Debug, PartialEq)]
+enum Condition {
+    /// `key=value`
+    Equal(String, String),
+    /// `key!=value`
+    NotEqual(String, String),
+    /// `key` or `key=*`
+    Exists(String),
+    /// `!key`
+    NotExists(String),
+    /// `key>number`
+    Greater(String, f64),
+    /// `key>=number`
+    GreaterEqual(String, f64),
+    /// `key<number`
+    Less(String, f64),
+    /// `key<=number`
+    LessEqual(String, f64),
+}
+
+impl Condition {
+    fn parse(text: &str) -> Result<Condition> {
+        let text = text.trim();
+
+        let condition = if let Some(key) = text.strip_prefix('!') {
+            Condition::NotExists(key.to_string())
+        } else if let Some((key, value)) = text.split_once("!=") {
+            Condition::NotEqual(key.to_string(), value.to_string())
+        } else if let Some((key, value)) = text.split_once(">=") {
+            Condition::GreaterEqual(key.to_string(), parse_number(value)?)
+        } else if let Some((key, value)) = text.split_once("<=") {
+            Condition::LessEqual(key.to_string(), parse_number(value)?)
+        } else if let Some((key, value)) = text.split_once('>') {
+            Condition::Greater(key.to_string(), parse_number(value)?)
+        } else if let Some((key, value)) = text.split_once('<') {
+            Condition::Less(key.to_string(), parse_number(value)?)
+        } else if let Some((key, "*")) = text.split_once('=') {
+            Condition::Exists(key.to_string())
+        } else if let Some((key, value)) = text.split_once('=') {
+            Condition::Equal(key.to_string(), value.to_string())
         } else {
-            None
+            Condition::Exists(text.to_string())
+        };
+
+        if condition.key().is_empty() {
+            bail!("missing key in '{}'", text);
+        }
+
+        Ok(condition)
+    }
+
+    fn key(&self) -> &str {
+        match self {
+            Condition::Equal(key, _)
+            | Condition::NotEqual(key, _)
+            | Condition::Exists(key)
+            | Condition::NotExists(key)
+            | Condition::Greater(key, _)
+            | Condition::GreaterEqual(key, _)
+            | Condition::Less(key, _)
+            | Condition::LessEqual(key, _) => key,
+        }
+    }
+
+    /// `highway=primary` becomes `highway-primary`, `building:levels>3`
+    /// becomes `building-levels-gt-3`
+    fn class_name(&self) -> String {
+        let name = match self {
+            Condition::Equal(key, value) => format!("{}-{}", key, value),
+            Condition::NotEqual(key, value) => format!("{}-not-{}", key, value),
+            Condition::Exists(key) => key.clone(),
+            Condition::NotExists(key) => format!("no-{}", key),
+            Condition::Greater(key, n) => format!("{}-gt-{}", key, n),
+            Condition::GreaterEqual(key, n) => format!("{}-ge-{}", key, n),
+            Condition::Less(key, n) => format!("{}-lt-{}", key, n),
+            Condition::LessEqual(key, n) => format!("{}-le-{}", key, n),
+        };
+
+        name.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect()
+    }
+
+    /// A condition on the value is more specific than only a test on the key
+    fn on_value(&self) -> bool {
+        !matches!(self, Condition::Exists(_) | Condition::NotExists(_))
+    }
+
+    fn matches(&self, tags: &[(&str, &str)]) -> bool {
+        let value = tags
+            .iter()
+            .find(|&&(key, _)| key == self.key())
+            .map(|&(_, value)| value);
+
+        let number = || value.and_then(|v| v.trim().parse::<f64>().ok());
+
+        match self {
+            Condition::Equal(_, v) => value == Some(v),
+            Condition::NotEqual(_, v) => value != Some(v),
+            Condition::Exists(_) => value.is_some(),
+            Condition::NotExists(_) => value.is_none(),
+            Condition::Greater(_, n) => number().is_some_and(|x| x > *n),
+            Condition::GreaterEqual(_, n) => number().is_some_and(|x| x >= *n),
+            Condition::Less(_, n) => number().is_some_and(|x| x < *n),
+            Condition::LessEqual(_, n) => number().is_some_and(|x| x <= *n),
         }
     }
 }
 
+impl Display for Condition {
+    /// The condition as it is written in the style, `building:levels>3`
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Condition::Equal(key, value) => write!(f, "{}={}", key, value),
+            Condition::NotEqual(key, value) => write!(f, "{}!={}", key, value),
+            Condition::Exists(key) => write!(f, "{}", key),
+            Condition::NotExists(key) => write!(f, "!{}", key),
+            Condition::Greater(key, n) => write!(f, "{}>{}", key, n),
+            Condition::GreaterEqual(key, n) => write!(f, "{}>={}", key, n),
+            Condition::Less(key, n) => write!(f, "{}<{}", key, n),
+            Condition::LessEqual(key, n) => write!(f, "{}<={}", key, n),
+        }
+    }
+}
+
+fn parse_number(text: &str) -> Result<f64> {
+    text.trim()
+        .parse()
+        .map_err(|_| anyhow!("'{}' is not a number", text))
+}
+
+struct Rule {
+    conditions: Vec<Condition>,
+    /// css class of the style, unique in the style file
+    class: String,
+    /// index in [Style::layers]
+    layer: usize,
+    z_index: u32,
+    style: String,
+    symbol: Option<String>,
+    size: Option<f64>,
+    label: Option<String>,
+    halo: Option<String>,
+    importance: u32,
+    shield: Option<String>,
+    casing: Option<String>,
+    /// caption in the legend, empty to leave the rule out
+    legend: Option<String>,
+}
+
+impl Rule {
+    /// Rules with more conditions, and then with more conditions on the value,
+    /// are more specific
+    fn specificity(&self) -> (usize, usize) {
+        (
+            self.conditions.len(),
+            self.conditions.iter().filter(|c| c.on_value()).count(),
+        )
+    }
+
+    fn matches(&self, tags: &[(&str, &str)]) -> bool {
+        self.conditions.iter().all(|c| c.matches(tags))
+    }
+
+    /// Rules with a symbol or without a style are for nodes, the others for
+    /// ways and relations
+    fn is_node(&self) -> bool {
+        self.symbol.is_some() || self.style.trim().is_empty()
+    }
+
+    /// The key of the first condition on a tag of `tags`
+    fn key(&self, tags: &[(&str, &str)]) -> Option<&str> {
+        self.conditions
+            .iter()
+            .map(|c| c.key())
+            .find(|&key| tags.iter().any(|&(k, _)| k == key))
+    }
+
+    /// The legend caption, or else the tags, and the swatch of the rule
+    fn legend_entry(&self) -> LegendEntry<'_> {
+        let tags = self
+            .conditions
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let has_style = !self.style.trim().is_empty();
+        let swatch = match &self.symbol {
+            Some(name) => Swatch::Symbol {
+                name,
+                class: has_style,
+            },
+            None if !has_style => Swatch::Text,
+            None if is_filled(&self.style) => Swatch::Area,
+            None => Swatch::Line {
+                casing: self.casing.is_some(),
+            },
+        };
+
+        LegendEntry {
+            caption: self.legend.clone().unwrap_or_else(|| tags.clone()),
+            tags,
+            class: &self.class,
+            swatch,
+        }
+    }
+
+    fn label(&self) -> Option<Label<'_>> {
+        Some(Label {
+            class: &self.class,
+            style: self.label.as_deref()?,
+            halo: self.halo.as_deref(),
+            importance: self.importance,
+        })
+    }
+}
+
+/// Everything the rule of a way sets
+pub struct WayStyle<'a> {
+    /// index of the rule, see [Style::patterns]
+    pub rule: usize,
+    /// css class of the style, `{class}-casing` for the casing
+    pub class: &'a str,
+    /// the first key of the rule the way has, for the data attributes
+    pub key: Option<&'a str>,
+    /// index of the layer group
+    pub layer: usize,
+    pub z_index: u32,
+    pub style: &'a str,
+    /// drawn beneath the style, wider so it shows as an outline
+    pub casing: Option<&'a str>,
+    pub label: Option<Label<'a>>,
+    /// road class of the shields
+    pub shield: Option<&'a str>,
+}
+
+/// The symbol and label of a node
+pub struct NodeStyle<'a> {
+    /// index of the rule, see [Style::patterns]
+    pub rule: usize,
+    /// css class of the style, empty without a style
+    pub class: &'a str,
+    /// the first key of the rule the node has, for the data attributes
+    pub key: Option<&'a str>,
+    /// index of the layer group
+    pub layer: usize,
+    pub z_index: u32,
+    pub symbol: Option<&'a str>,
+    pub size: Option<f64>,
+    pub label: Option<Label<'a>>,
+}
+
+/// A line of the legend
+pub struct LegendEntry<'a> {
+    /// the legend of the rule, or else the tags
+    pub caption: String,
+    /// the conditions of the rule, `building, !disused`
+    pub tags: String,
+    /// css class of the rule
+    pub class: &'a str,
+    pub swatch: Swatch<'a>,
+}
+
+/// How the legend shows a rule
+#[derive(Debug, PartialEq)]
+pub enum Swatch<'a> {
+    /// a square with the fill
+    Area,
+    /// a short line, on the casing when it has one
+    Line { casing: bool },
+    /// the symbol, in the style of the rule when it has one
+    Symbol { name: &'a str, class: bool },
+    /// a sample text in the label style, for the rules with only a label
+    Text,
+}
+
+/// The text style of the name of a way, area or node
+pub struct Label<'a> {
+    /// css class of the rule, the text gets `{class}-label`
+    pub class: &'a str,
+    pub style: &'a str,
+    pub halo: Option<&'a str>,
+    /// labels with a higher importance are placed first
+    pub importance: u32,
+}
+
+pub struct Style {
+    rules: Vec<Rule>,
+    /// The groups of the svg in drawing order, ending with the grid, the
+    /// labels and the furniture unless the style puts them elsewhere
+    layers: Vec<String>,
+    pub grid: GridStyle,
+    /// The directory of the style file, None for the built in style
+    dir: Option<PathBuf>,
+}
+
+impl Style {
+    /// The built in style `style/default.toml`
+    pub fn new() -> Style {
+        Style::parse(include_str!("../style/default.toml")).expect("invalid built in style")
+    }
+
+    pub fn from_file(path: &str) -> Result<Style> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Can not read style file {}", path))?;
+
+        let dir = Path::new(path).parent().map(Path::to_path_buf);
+
+        Style::parse_in(&text, dir).with_context(|| format!("Invalid style file {}", path))
+    }
+
+    pub fn parse(text: &str) -> Result<Style> {
+        Style::parse_in(text, None)
+    }
+
+    /// The style with its svg files in `dir`, or only the built in ones
+    fn parse_in(text: &str, dir: Option<PathBuf>) -> Result<Style> {
+        let file: StyleFile = toml::from_str(text)?;
+
+        let mut layers = file
+            .layers
+            .unwrap_or_else(|| vec![DEFAULT_LAYER.to_string()]);
+
+        if layers.is_empty() {
+            bail!("no layers");
+        }
+
+        for (i, name) in layers.iter().enumerate() {
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                bail!(
+                    "invalid layer name '{}', use letters, digits, - and _",
+                    name
+                );
+            }
+
+            if layers[..i].contains(name) {
+                bail!("duplicate layer {}", name);
+            }
+        }
+
+        for name in [GRID, LABELS, FURNITURE] {
+            if !layers.iter().any(|layer| layer == name) {
+                layers.push(name.to_string());
+            }
+        }
+
+        let mut style = Style {
+            rules: Vec::new(),
+            layers,
+            grid: file.grid,
+            dir,
+        };
+
+        for spacing in [style.grid.spacing, style.grid.graticule] {
+            if !spacing.is_finite() || spacing < 0.0 {
+                bail!("invalid grid spacing {}, use 0 or more", spacing);
+            }
+        }
+
+        for rule in file.rule {
+            let line = line_number(text, rule.span().start);
+            let rule = rule.into_inner();
 
+            let conditions = match (&rule.key, &rule.value, &rule.tags) {
+                (Some(key), Some(value), None) => {
+                    vec![Condition::Equal(key.clone(), value.clone())]
+                }
+                (Some(key), None, None) => vec![Condition::Exists(key.clone())],
+                (None, None, Some(tags)) if !tags.is_empty() => tags
+                    .iter()
+                    .map(|tag| Condition::parse(tag))
+                    .collect::<Result<_>>()
+                    .with_context(|| format!("line {}: invalid tags", line))?,
+                _ => bail!("line {}: a rule needs a key (and value) or tags", line),
+            };
 
-// <defs>
-// <pattern       width="256"       height="256"       id="leaftype_broadleaved"       style="fill:#6b8d5e">
-// <path d="M 154 0 L 154 5 L 155 5 L 155 0 L 154 0 z M 190 0 L 190 3 L 191 3 L 191 0 L 190 0 z M 248 0 L 248 1 L 249 1 L 249 0 L 248 0 z M 116.5 4 C 114.52791 4 113 5.8354804 113 8 C 113 9.9757872 114.27809 11.659686 116 11.941406 L 116 17 L 117 17 L 117 11.941406 C 118.72191 11.659686 120 9.9757872 120 8 C 120 5.8354804 118.47209 4 116.5 4 z M 116.5 5 C 117.8416 5 119 6.2987525 119 8 C 119 9.7012475 117.8416 11 116.5 11 C 115.1584 11 114 9.7012475 114 8 C 114 6.2987525 115.1584 5 116.5 5 z M 50.5 6 C 48.527905 6 47 7.8354804 47 10 C 47 11.975787 48.278087 13.659686 50 13.941406 L 50 19 L 51 19 L 51 13.941406 C 52.721913 13.659686 54 11.975787 54 10 C 54 7.8354804 52.472095 6 50.5 6 z M 50.5 7 C 51.841598 7 53 8.2987525 53 10 C 53 11.701248 51.841598 13 50.5 13 C 49.158402 13 48 11.701248 48 10 C 48 8.2987525 49.158402 7 50.5 7 z M 13.5 11 C 11.527905 11 10 12.83548 10 15 C 10 16.975787 11.278087 18.659686 13 18.941406 L 13 24 L 14 24 L 14 18.941406 C 15.721913 18.659686 17 16.975787 17 15 C 17 12.83548 15.472095 11 13.5 11 z M 13.5 12 C 14.841598 12 16 13.298752 16 15 C 16 16.701247 14.841598 18 13.5 18 C 12.158402 18 11 16.701247 11 15 C 11 13.298752 12.158402 12 13.5 12 z M 215.5 12 C 213.5279 12 212 13.83548 212 16 C 212 17.975787 213.27809 19.659686 215 19.941406 L 215 25 L 216 25 L 216 19.941406 C 217.72191 19.659686 219 17.975787 219 16 C 219 13.83548 217.47209 12 215.5 12 z M 215.5 13 C 216.8416 13 218 14.298752 218 16 C 218 17.701247 216.8416 19 215.5 19 C 214.1584 19 213 17.701247 213 16 C 213 14.298752 214.1584 13 215.5 13 z M 178.5 18 C 176.5279 18 175 19.83548 175 22 C 175 23.975787 176.27809 25.659686 178 25.941406 L 178 31 L 179 31 L 179 25.941406 C 180.72191 25.659686 182 23.975787 182 22 C 182 19.83548 180.47209 18 178.5 18 z M 76.5 19 C 74.527905 19 73 20.83548 73 23 C 73 24.975787 74.278087 26.659686 76 26.941406 L 76 32 L 77 32 L 77 26.941406 C 78.721913 26.659686 80 24.975787 80 23 C 80 20.83548 78.472095 19 76.5 19 z M 178.5 19 C 179.8416 19 181 20.298752 181 22 C 181 23.701247 179.8416 25 178.5 25 C 177.1584 25 176 23.701247 176 22 C 176 20.298752 177.1584 19 178.5 19 z M 76.5 20 C 77.841598 20 79 21.298752 79 23 C 79 24.701247 77.841598 26 76.5 26 C 75.158402 26 74 24.701247 74 23 C 74 21.298752 75.158402 20 76.5 20 z M 137.5 22 C 135.5279 22 134 23.83548 134 26 C 134 27.975787 135.27809 29.659686 137 29.941406 L 137 35 L 138 35 L 138 29.941406 C 139.72191 29.659686 141 27.975787 141 26 C 141 23.83548 139.47209 22 137.5 22 z M 137.5 23 C 138.8416 23 140 24.298752 140 26 C 140 27.701247 138.8416 29 137.5 29 C 136.1584 29 135 27.701247 135 26 C 135 24.298752 136.1584 23 137.5 23 z M 241.5 28 C 239.5279 28 238 29.83548 238 32 C 238 33.975787 239.27809 35.659686 241 35.941406 L 241 41 L 242 41 L 242 35.941406 C 243.72191 35.659686 245 33.975787 245 32 C 245 29.83548 243.47209 28 241.5 28 z M 241.5 29 C 242.8416 29 244 30.298752 244 32 C 244 33.701247 242.8416 35 241.5 35 C 240.1584 35 239 33.701247 239 32 C 239 30.298752 240.1584 29 241.5 29 z M 45.5 39 C 43.527905 39 42 40.83548 42 43 C 42 44.975787 43.278087 46.659686 45 46.941406 L 45 52 L 46 52 L 46 46.941406 C 47.721913 46.659686 49 44.975787 49 43 C 49 40.83548 47.472095 39 45.5 39 z M 45.5 40 C 46.841598 40 48 41.298752 48 43 C 48 44.701247 46.841598 46 45.5 46 C 44.158402 46 43 44.701247 43 43 C 43 41.298752 44.158402 40 45.5 40 z M 113.5 41 C 111.52791 41 110 42.83548 110 45 C 110 46.975787 111.27809 48.659686 113 48.941406 L 113 54 L 114 54 L 114 48.941406 C 115.72191 48.659686 117 46.975787 117 45 C 117 42.83548 115.47209 41 113.5 41 z M 113.5 42 C 114.8416 42 116 43.298752 116 45 C 116 46.701247 114.8416 48 113.5 48 C 112.1584 48 111 46.701247 111 45 C 111 43.298752 112.1584 42 113.5 42 z M 156.5 44 C 154.5279 44 153 45.83548 153 48 C 153 49.975787 154.27809 51.659686 156 51.941406 L 156 57 L 157 57 L 157 51.941406 C 158.72191 51.659686 160 49.975787 160 48 C 160 45.83548 158.47209 44 156.5 44 z M 156.5 45 C 157.8416 45 159 46.298752 159 48 C 159 49.701247 157.8416 51 156.5 51 C 155.1584 51 154 49.701247 154 48 C 154 46.298752 155.1584 45 156.5 45 z M 220.5 46 C 218.5279 46 217 47.83548 217 50 C 217 51.975787 218.27809 53.659686 220 53.941406 L 220 59 L 221 59 L 221 53.941406 C 222.72191 53.659686 224 51.975787 224 50 C 224 47.83548 222.47209 46 220.5 46 z M 220.5 47 C 221.8416 47 223 48.298752 223 50 C 223 51.701247 221.8416 53 220.5 53 C 219.1584 53 218 51.701247 218 50 C 218 48.298752 219.1584 47 220.5 47 z M 74.5 48 C 72.527905 48 71 49.83548 71 52 C 71 53.975787 72.278087 55.659686 74 55.941406 L 74 61 L 75 61 L 75 55.941406 C 76.721913 55.659686 78 53.975787 78 52 C 78 49.83548 76.472095 48 74.5 48 z M 10.5 49 C 8.527905 49 7 50.83548 7 53 C 7 54.975787 8.278087 56.659686 10 56.941406 L 10 62 L 11 62 L 11 56.941406 C 12.721913 56.659686 14 54.975787 14 53 C 14 50.83548 12.472095 49 10.5 49 z M 74.5 49 C 75.841598 49 77 50.298752 77 52 C 77 53.701247 75.841598 55 74.5 55 C 73.158402 55 72 53.701247 72 52 C 72 50.298752 73.158402 49 74.5 49 z M 10.5 50 C 11.841598 50 13 51.298752 13 53 C 13 54.701247 11.841598 56 10.5 56 C 9.1584018 56 8 54.701247 8 53 C 8 51.298752 9.1584018 50 10.5 50 z M 182.5 53 C 180.5279 53 179 54.83548 179 57 C 179 58.975787 180.27809 60.659686 182 60.941406 L 182 66 L 183 66 L 183 60.941406 C 184.72191 60.659686 186 58.975787 186 57 C 186 54.83548 184.47209 53 182.5 53 z M 182.5 54 C 183.8416 54 185 55.298752 185 57 C 185 58.701247 183.8416 60 182.5 60 C 181.1584 60 180 58.701247 180 57 C 180 55.298752 181.1584 54 182.5 54 z M 35.5 68 C 33.527905 68 32 69.83548 32 72 C 32 73.975787 33.278087 75.659685 35 75.941406 L 35 81 L 36 81 L 36 75.941406 C 37.721913 75.659685 39 73.975787 39 72 C 39 69.83548 37.472095 68 35.5 68 z M 35.5 69 C 36.841598 69 38 70.298753 38 72 C 38 73.701247 36.841598 75 35.5 75 C 34.158402 75 33 73.701247 33 72 C 33 70.298753 34.158402 69 35.5 69 z M 101.5 69 C 99.527905 69 98 70.83548 98 73 C 98 74.975787 99.278087 76.659685 101 76.941406 L 101 82 L 102 82 L 102 76.941406 C 103.72191 76.659685 105 74.975787 105 73 C 105 70.83548 103.47209 69 101.5 69 z M 101.5 70 C 102.8416 70 104 71.298753 104 73 C 104 74.701247 102.8416 76 101.5 76 C 100.1584 76 99 74.701247 99 73 C 99 71.298753 100.1584 70 101.5 70 z M 134.5 71 C 132.5279 71 131 72.83548 131 75 C 131 76.975787 132.27809 78.659685 134 78.941406 L 134 84 L 135 84 L 135 78.941406 C 136.72191 78.659685 138 76.975787 138 75 C 138 72.83548 136.47209 71 134.5 71 z M 134.5 72 C 135.8416 72 137 73.298753 137 75 C 137 76.701247 135.8416 78 134.5 78 C 133.1584 78 132 76.701247 132 75 C 132 73.298753 133.1584 72 134.5 72 z M 207.5 75 C 205.5279 75 204 76.83548 204 79 C 204 80.975787 205.27809 82.659685 207 82.941406 L 207 88 L 208 88 L 208 82.941406 C 209.72191 82.659685 211 80.975787 211 79 C 211 76.83548 209.47209 75 207.5 75 z M 207.5 76 C 208.8416 76 210 77.298753 210 79 C 210 80.701247 208.8416 82 207.5 82 C 206.1584 82 205 80.701247 205 79 C 205 77.298753 206.1584 76 207.5 76 z M 164.5 79 C 162.5279 79 161 80.83548 161 83 C 161 84.975787 162.27809 86.659685 164 86.941406 L 164 92 L 165 92 L 165 86.941406 C 166.72191 86.659685 168 84.975787 168 83 C 168 80.83548 166.47209 79 164.5 79 z M 164.5 80 C 165.8416 80 167 81.298753 167 83 C 167 84.701247 165.8416 86 164.5 86 C 163.1584 86 162 84.701247 162 83 C 162 81.298753 163.1584 80 164.5 80 z M 74.5 82 C 72.527905 82 71 83.83548 71 86 C 71 87.975787 72.278087 89.659685 74 89.941406 L 74 95 L 75 95 L 75 89.941406 C 76.721913 89.659685 78 87.975787 78 86 C 78 83.83548 76.472095 82 74.5 82 z M 74.5 83 C 75.841598 83 77 84.298753 77 86 C 77 87.701247 75.841598 89 74.5 89 C 73.158402 89 72 87.701247 72 86 C 72 84.298753 73.158402 83 74.5 83 z M 237.5 85 C 235.5279 85 234 86.83548 234 89 C 234 90.975787 235.27809 92.659685 237 92.941406 L 237 98 L 238 98 L 238 92.941406 C 239.72191 92.659685 241 90.975787 241 89 C 241 86.83548 239.47209 85 237.5 85 z M 11.5 86 C 9.527905 86 8 87.83548 8 90 C 8 91.975787 9.278087 93.659685 11 93.941406 L 11 99 L 12 99 L 12 93.941406 C 13.721913 93.659685 15 91.975787 15 90 C 15 87.83548 13.472095 86 11.5 86 z M 237.5 86 C 238.8416 86 240 87.298753 240 89 C 240 90.701247 238.8416 92 237.5 92 C 236.1584 92 235 90.701247 235 89 C 235 87.298753 236.1584 86 237.5 86 z M 11.5 87 C 12.841598 87 14 88.298753 14 90 C 14 91.701247 12.841598 93 11.5 93 C 10.158402 93 9 91.701247 9 90 C 9 88.298753 10.158402 87 11.5 87 z M 186.5 100 C 184.5279 100 183 101.83548 183 104 C 183 105.97579 184.27809 107.65969 186 107.94141 L 186 113 L 187 113 L 187 107.94141 C 188.72191 107.65969 190 105.97579 190 104 C 190 101.83548 188.47209 100 186.5 100 z M 48.5 101 C 46.527905 101 45 102.83548 45 105 C 45 106.97579 46.278087 108.65969 48 108.94141 L 48 114 L 49 114 L 49 108.94141 C 50.721913 108.65969 52 106.97579 52 105 C 52 102.83548 50.472095 101 48.5 101 z M 110.5 101 C 108.52791 101 107 102.83548 107 105 C 107 106.97579 108.27809 108.65969 110 108.94141 L 110 114 L 111 114 L 111 108.94141 C 112.72191 108.65969 114 106.97579 114 105 C 114 102.83548 112.47209 101 110.5 101 z M 186.5 101 C 187.8416 101 189 102.29875 189 104 C 189 105.70125 187.8416 107 186.5 107 C 185.1584 107 184 105.70125 184 104 C 184 102.29875 185.1584 101 186.5 101 z M 48.5 102 C 49.841598 102 51 103.29875 51 105 C 51 106.70125 49.841598 108 48.5 108 C 47.158402 108 46 106.70125 46 105 C 46 103.29875 47.158402 102 48.5 102 z M 110.5 102 C 111.8416 102 113 103.29875 113 105 C 113 106.70125 111.8416 108 110.5 108 C 109.1584 108 108 106.70125 108 105 C 108 103.29875 109.1584 102 110.5 102 z M 146.5 107 C 144.5279 107 143 108.83548 143 111 C 143 112.97579 144.27809 114.65969 146 114.94141 L 146 120 L 147 120 L 147 114.94141 C 148.72191 114.65969 150 112.97579 150 111 C 150 108.83548 148.47209 107 146.5 107 z M 146.5 108 C 147.8416 108 149 109.29875 149 111 C 149 112.70125 147.8416 114 146.5 114 C 145.1584 114 144 112.70125 144 111 C 144 109.29875 145.1584 108 146.5 108 z M 216.5 110 C 214.5279 110 213 111.83548 213 114 C 213 115.97579 214.27809 117.65969 216 117.94141 L 216 123 L 217 123 L 217 117.94141 C 218.72191 117.65969 220 115.97579 220 114 C 220 111.83548 218.47209 110 216.5 110 z M 82.5 111 C 80.527905 111 79 112.83548 79 115 C 79 116.97579 80.278087 118.65969 82 118.94141 L 82 124 L 83 124 L 83 118.94141 C 84.721913 118.65969 86 116.97579 86 115 C 86 112.83548 84.472095 111 82.5 111 z M 216.5 111 C 217.8416 111 219 112.29875 219 114 C 219 115.70125 217.8416 117 216.5 117 C 215.1584 117 214 115.70125 214 114 C 214 112.29875 215.1584 111 216.5 111 z M 82.5 112 C 83.841598 112 85 113.29875 85 115 C 85 116.70125 83.841598 118 82.5 118 C 81.158402 118 80 116.70125 80 115 C 80 113.29875 81.158402 112 82.5 112 z M 18.5 113 C 16.527905 113 15 114.83548 15 117 C 15 118.97579 16.278087 120.65969 18 120.94141 L 18 126 L 19 126 L 19 120.94141 C 20.721913 120.65969 22 118.97579 22 117 C 22 114.83548 20.472095 113 18.5 113 z M 18.5 114 C 19.841598 114 21 115.29875 21 117 C 21 118.70125 19.841598 120 18.5 120 C 17.158402 120 16 118.70125 16 117 C 16 115.29875 17.158402 114 18.5 114 z M 245.5 123 C 243.5279 123 242 124.83548 242 127 C 242 128.97579 243.27809 130.65969 245 130.94141 L 245 136 L 246 136 L 246 130.94141 C 247.72191 130.65969 249 128.97579 249 127 C 249 124.83548 247.47209 123 245.5 123 z M 245.5 124 C 246.8416 124 248 125.29875 248 127 C 248 128.70125 246.8416 130 245.5 130 C 244.1584 130 243 128.70125 243 127 C 243 125.29875 244.1584 124 245.5 124 z M 168.5 125 C 166.5279 125 165 126.83548 165 129 C 165 130.97579 166.27809 132.65969 168 132.94141 L 168 138 L 169 138 L 169 132.94141 C 170.72191 132.65969 172 130.97579 172 129 C 172 126.83548 170.47209 125 168.5 125 z M 168.5 126 C 169.8416 126 171 127.29875 171 129 C 171 130.70125 169.8416 132 168.5 132 C 167.1584 132 166 130.70125 166 129 C 166 127.29875 167.1584 126 168.5 126 z M 114.5 132 C 112.52791 132 111 133.83548 111 136 C 111 137.97579 112.27809 139.65969 114 139.94141 L 114 145 L 115 145 L 115 139.94141 C 116.72191 139.65969 118 137.97579 118 136 C 118 133.83548 116.47209 132 114.5 132 z M 39.5 133 C 37.527905 133 36 134.83548 36 137 C 36 138.97579 37.278087 140.65969 39 140.94141 L 39 146 L 40 146 L 40 140.94141 C 41.721913 140.65969 43 138.97579 43 137 C 43 134.83548 41.472095 133 39.5 133 z M 114.5 133 C 115.8416 133 117 134.29875 117 136 C 117 137.70125 115.8416 139 114.5 139 C 113.1584 139 112 137.70125 112 136 C 112 134.29875 113.1584 133 114.5 133 z M 39.5 134 C 40.841598 134 42 135.29875 42 137 C 42 138.70125 40.841598 140 39.5 140 C 38.158402 140 37 138.70125 37 137 C 37 135.29875 38.158402 134 39.5 134 z M 202.5 139 C 200.5279 139 199 140.83548 199 143 C 199 144.97579 200.27809 146.65969 202 146.94141 L 202 152 L 203 152 L 203 146.94141 C 204.72191 146.65969 206 144.97579 206 143 C 206 140.83548 204.47209 139 202.5 139 z M 202.5 140 C 203.8416 140 205 141.29875 205 143 C 205 144.70125 203.8416 146 202.5 146 C 201.1584 146 200 144.70125 200 143 C 200 141.29875 201.1584 140 202.5 140 z M 82.5 145 C 80.527905 145 79 146.83548 79 149 C 79 150.97579 80.278087 152.65969 82 152.94141 L 82 158 L 83 158 L 83 152.94141 C 84.721913 152.65969 86 150.97579 86 149 C 86 146.83548 84.472095 145 82.5 145 z M 82.5 146 C 83.841598 146 85 147.29875 85 149 C 85 150.70125 83.841598 152 82.5 152 C 81.158402 152 80 150.70125 80 149 C 80 147.29875 81.158402 146 82.5 146 z M 228.5 149 C 226.5279 149 225 150.83548 225 153 C 225 154.97579 226.27809 156.65969 228 156.94141 L 228 162 L 229 162 L 229 156.94141 C 230.72191 156.65969 232 154.97579 232 153 C 232 150.83548 230.47209 149 228.5 149 z M 11.5 150 C 9.527905 150 8 151.83548 8 154 C 8 155.97579 9.278087 157.65969 11 157.94141 L 11 163 L 12 163 L 12 157.94141 C 13.721913 157.65969 15 155.97579 15 154 C 15 151.83548 13.472095 150 11.5 150 z M 146.5 150 C 144.5279 150 143 151.83548 143 154 C 143 155.97579 144.27809 157.65969 146 157.94141 L 146 163 L 147 163 L 147 157.94141 C 148.72191 157.65969 150 155.97579 150 154 C 150 151.83548 148.47209 150 146.5 150 z M 228.5 150 C 229.8416 150 231 151.29875 231 153 C 231 154.70125 229.8416 156 228.5 156 C 227.1584 156 226 154.70125 226 153 C 226 151.29875 227.1584 150 228.5 150 z M 11.5 151 C 12.841598 151 14 152.29875 14 154 C 14 155.70125 12.841598 157 11.5 157 C 10.158402 157 9 155.70125 9 154 C 9 152.29875 10.158402 151 11.5 151 z M 146.5 151 C 147.8416 151 149 152.29875 149 154 C 149 155.70125 147.8416 157 146.5 157 C 145.1584 157 144 155.70125 144 154 C 144 152.29875 145.1584 151 146.5 151 z M 54.5 156 C 52.527905 156 51 157.83548 51 160 C 51 161.97579 52.278087 163.65969 54 163.94141 L 54 169 L 55 169 L 55 163.94141 C 56.721913 163.65969 58 161.97579 58 160 C 58 157.83548 56.472095 156 54.5 156 z M 54.5 157 C 55.841598 157 57 158.29875 57 160 C 57 161.70125 55.841598 163 54.5 163 C 53.158402 163 52 161.70125 52 160 C 52 158.29875 53.158402 157 54.5 157 z M 176.5 160 C 174.5279 160 173 161.83548 173 164 C 173 165.97579 174.27809 167.65969 176 167.94141 L 176 173 L 177 173 L 177 167.94141 C 178.72191 167.65969 180 165.97579 180 164 C 180 161.83548 178.47209 160 176.5 160 z M 176.5 161 C 177.8416 161 179 162.29875 179 164 C 179 165.70125 177.8416 167 176.5 167 C 175.1584 167 174 165.70125 174 164 C 174 162.29875 175.1584 161 176.5 161 z M 117.5 164 C 115.52791 164 114 165.83548 114 168 C 114 169.97579 115.27809 171.65969 117 171.94141 L 117 177 L 118 177 L 118 171.94141 C 119.72191 171.65969 121 169.97579 121 168 C 121 165.83548 119.47209 164 117.5 164 z M 117.5 165 C 118.8416 165 120 166.29875 120 168 C 120 169.70125 118.8416 171 117.5 171 C 116.1584 171 115 169.70125 115 168 C 115 166.29875 116.1584 165 117.5 165 z M 209.5 174 C 207.5279 174 206 175.83548 206 178 C 206 179.97579 207.27809 181.65969 209 181.94141 L 209 187 L 210 187 L 210 181.94141 C 211.72191 181.65969 213 179.97579 213 178 C 213 175.83548 211.47209 174 209.5 174 z M 87.5 175 C 85.527905 175 84 176.83548 84 179 C 84 180.97579 85.278087 182.65969 87 182.94141 L 87 188 L 88 188 L 88 182.94141 C 89.721913 182.65969 91 180.97579 91 179 C 91 176.83548 89.472095 175 87.5 175 z M 209.5 175 C 210.8416 175 212 176.29875 212 178 C 212 179.70125 210.8416 181 209.5 181 C 208.1584 181 207 179.70125 207 178 C 207 176.29875 208.1584 175 209.5 175 z M 26.5 176 C 24.527905 176 23 177.83548 23 180 C 23 181.97579 24.278087 183.65969 26 183.94141 L 26 189 L 27 189 L 27 183.94141 C 28.721913 183.65969 30 181.97579 30 180 C 30 177.83548 28.472095 176 26.5 176 z M 87.5 176 C 88.841598 176 90 177.29875 90 179 C 90 180.70125 88.841598 182 87.5 182 C 86.158402 182 85 180.70125 85 179 C 85 177.29875 86.158402 176 87.5 176 z M 26.5 177 C 27.841598 177 29 178.29875 29 180 C 29 181.70125 27.841598 183 26.5 183 C 25.158402 183 24 181.70125 24 180 C 24 178.29875 25.158402 177 26.5 177 z M 251.5 180 C 249.5279 180 248 181.83548 248 184 C 248 185.97579 249.27809 187.65969 251 187.94141 L 251 193 L 252 193 L 252 187.94141 C 253.72191 187.65969 255 185.97579 255 184 C 255 181.83548 253.47209 180 251.5 180 z M 251.5 181 C 252.8416 181 254 182.29875 254 184 C 254 185.70125 252.8416 187 251.5 187 C 250.1584 187 249 185.70125 249 184 C 249 182.29875 250.1584 181 251.5 181 z M 146.5 185 C 144.5279 185 143 186.83548 143 189 C 143 190.97579 144.27809 192.65969 146 192.94141 L 146 198 L 147 198 L 147 192.94141 C 148.72191 192.65969 150 190.97579 150 189 C 150 186.83548 148.47209 185 146.5 185 z M 146.5 186 C 147.8416 186 149 187.29875 149 189 C 149 190.70125 147.8416 192 146.5 192 C 145.1584 192 144 190.70125 144 189 C 144 187.29875 145.1584 186 146.5 186 z M 177.5 189 C 175.5279 189 174 190.83548 174 193 C 174 194.97579 175.27809 196.65969 177 196.94141 L 177 202 L 178 202 L 178 196.94141 C 179.72191 196.65969 181 194.97579 181 193 C 181 190.83548 179.47209 189 177.5 189 z M 177.5 190 C 178.8416 190 180 191.29875 180 193 C 180 194.70125 178.8416 196 177.5 196 C 176.1584 196 175 194.70125 175 193 C 175 191.29875 176.1584 190 177.5 190 z M 57.5 194 C 55.527905 194 54 195.83548 54 198 C 54 199.97579 55.278087 201.65969 57 201.94141 L 57 207 L 58 207 L 58 201.94141 C 59.721913 201.65969 61 199.97579 61 198 C 61 195.83548 59.472095 194 57.5 194 z M 57.5 195 C 58.841598 195 60 196.29875 60 198 C 60 199.70125 58.841598 201 57.5 201 C 56.158402 201 55 199.70125 55 198 C 55 196.29875 56.158402 195 57.5 195 z M 113.5 196 C 111.52791 196 110 197.83548 110 200 C 110 201.97579 111.27809 203.65969 113 203.94141 L 113 209 L 114 209 L 114 203.94141 C 115.72191 203.65969 117 201.97579 117 200 C 117 197.83548 115.47209 196 113.5 196 z M 113.5 197 C 114.8416 197 116 198.29875 116 200 C 116 201.70125 114.8416 203 113.5 203 C 112.1584 203 111 201.70125 111 200 C 111 198.29875 112.1584 197 113.5 197 z M 234.5 205 C 232.5279 205 231 206.83548 231 209 C 231 210.97579 232.27809 212.65969 234 212.94141 L 234 218 L 235 218 L 235 212.94141 C 236.72191 212.65969 238 210.97579 238 209 C 238 206.83548 236.47209 205 234.5 205 z M 234.5 206 C 235.8416 206 237 207.29875 237 209 C 237 210.70125 235.8416 212 234.5 212 C 233.1584 212 232 210.70125 232 209 C 232 207.29875 233.1584 206 234.5 206 z M 15.5 207 C 13.527905 207 12 208.83548 12 211 C 12 212.97579 13.278087 214.65969 15 214.94141 L 15 220 L 16 220 L 16 214.94141 C 17.721913 214.65969 19 212.97579 19 211 C 19 208.83548 17.472095 207 15.5 207 z M 15.5 208 C 16.841598 208 18 209.29875 18 211 C 18 212.70125 16.841598 214 15.5 214 C 14.158402 214 13 212.70125 13 211 C 13 209.29875 14.158402 208 15.5 208 z M 203.5 208 C 201.5279 208 200 209.83548 200 212 C 200 213.97579 201.27809 215.65969 203 215.94141 L 203 221 L 204 221 L 204 215.94141 C 205.72191 215.65969 207 213.97579 207 212 C 207 209.83548 205.47209 208 203.5 208 z M 203.5 209 C 204.8416 209 206 210.29875 206 212 C 206 213.70125 204.8416 215 203.5 215 C 202.1584 215 201 213.70125 201 212 C 201 210.29875 202.1584 209 203.5 209 z M 79.5 211 C 77.527905 211 76 212.83548 76 215 C 76 216.97579 77.278087 218.65969 79 218.94141 L 79 224 L 80 224 L 80 218.94141 C 81.721913 218.65969 83 216.97579 83 215 C 83 212.83548 81.472095 211 79.5 211 z M 79.5 212 C 80.841598 212 82 213.29875 82 215 C 82 216.70125 80.841598 218 79.5 218 C 78.158402 218 77 216.70125 77 215 C 77 213.29875 78.158402 212 79.5 212 z M 139.5 214 C 137.5279 214 136 215.83548 136 218 C 136 219.97579 137.27809 221.65969 139 221.94141 L 139 227 L 140 227 L 140 221.94141 C 141.72191 221.65969 143 219.97579 143 218 C 143 215.83548 141.47209 214 139.5 214 z M 139.5 215 C 140.8416 215 142 216.29875 142 218 C 142 219.70125 140.8416 221 139.5 221 C 138.1584 221 137 219.70125 137 218 C 137 216.29875 138.1584 215 139.5 215 z M 45.5 220 C 43.527905 220 42 221.83548 42 224 C 42 225.97579 43.278087 227.65969 45 227.94141 L 45 233 L 46 233 L 46 227.94141 C 47.721913 227.65969 49 225.97579 49 224 C 49 221.83548 47.472095 220 45.5 220 z M 45.5 221 C 46.841598 221 48 222.29875 48 224 C 48 225.70125 46.841598 227 45.5 227 C 44.158402 227 43 225.70125 43 224 C 43 222.29875 44.158402 221 45.5 221 z M 169.5 223 C 167.5279 223 166 224.83548 166 227 C 166 228.97579 167.27809 230.65969 169 230.94141 L 169 236 L 170 236 L 170 230.94141 C 171.72191 230.65969 173 228.97579 173 227 C 173 224.83548 171.47209 223 169.5 223 z M 169.5 224 C 170.8416 224 172 225.29875 172 227 C 172 228.70125 170.8416 230 169.5 230 C 168.1584 230 167 228.70125 167 227 C 167 225.29875 168.1584 224 169.5 224 z M 107.5 234 C 105.52791 234 104 235.83548 104 238 C 104 239.97579 105.27809 241.65969 107 241.94141 L 107 247 L 108 247 L 108 241.94141 C 109.72191 241.65969 111 239.97579 111 238 C 111 235.83548 109.47209 234 107.5 234 z M 107.5 235 C 108.8416 235 110 236.29875 110 238 C 110 239.70125 108.8416 241 107.5 241 C 106.1584 241 105 239.70125 105 238 C 105 236.29875 106.1584 235 107.5 235 z M 218.5 238 C 216.5279 238 215 239.83548 215 242 C 215 243.97579 216.27809 245.65969 218 245.94141 L 218 251 L 219 251 L 219 245.94141 C 220.72191 245.65969 222 243.97579 222 242 C 222 239.83548 220.47209 238 218.5 238 z M 218.5 239 C 219.8416 239 221 240.29875 221 242 C 221 243.70125 219.8416 245 218.5 245 C 217.1584 245 216 243.70125 216 242 C 216 240.29875 217.1584 239 218.5 239 z M 22.5 240 C 20.527905 240 19 241.83548 19 244 C 19 245.97579 20.278087 247.65969 22 247.94141 L 22 253 L 23 253 L 23 247.94141 C 24.721913 247.65969 26 245.97579 26 244 C 26 241.83548 24.472095 240 22.5 240 z M 22.5 241 C 23.841598 241 25 242.29875 25 244 C 25 245.70125 23.841598 247 22.5 247 C 21.158402 247 20 245.70125 20 244 C 20 242.29875 21.158402 241 22.5 241 z M 81.5 242 C 79.527905 242 78 243.83548 78 246 C 78 247.97579 79.278087 249.65969 81 249.94141 L 81 255 L 82 255 L 82 249.94141 C 83.721913 249.65969 85 247.97579 85 246 C 85 243.83548 83.472095 242 81.5 242 z M 81.5 243 C 82.841598 243 84 244.29875 84 246 C 84 247.70125 82.841598 249 81.5 249 C 80.158402 249 79 247.70125 79 246 C 79 244.29875 80.158402 243 81.5 243 z M 248.5 244 C 246.5279 244 245 245.83548 245 248 C 245 249.97579 246.27809 251.65969 248 251.94141 L 248 256 L 249 256 L 249 251.94141 C 250.72191 251.65969 252 249.97579 252 248 C 252 245.83548 250.47209 244 248.5 244 z M 248.5 245 C 249.8416 245 251 246.29875 251 248 C 251 249.70125 249.8416 251 248.5 251 C 247.1584 251 246 249.70125 246 248 C 246 246.29875 247.1584 245 248.5 245 z M 190.5 246 C 188.5279 246 187 247.83548 187 250 C 187 251.97579 188.27809 253.65969 190 253.94141 L 190 256 L 191 256 L 191 253.94141 C 192.72191 253.65969 194 251.97579 194 250 C 194 247.83548 192.47209 246 190.5 246 z M 190.5 247 C 191.8416 247 193 248.29875 193 250 C 193 251.70125 191.8416 253 190.5 253 C 189.1584 253 188 251.70125 188 250 C 188 248.29875 189.1584 247 190.5 247 z M 154.5 248 C 152.5279 248 151 249.83548 151 252 C 151 253.97579 152.27809 255.65969 154 255.94141 L 154 256 L 155 256 L 155 255.94141 C 156.72191 255.65969 158 253.97579 158 252 C 158 249.83548 156.47209 248 154.5 248 z M 154.5 249 C 155.8416 249 157 250.29875 157 252 C 157 253.70125 155.8416 255 154.5 255 C 153.1584 255 152 253.70125 152 252 C 152 250.29875 153.1584 249 154.5 249 z "/>
-// </pattern>
-// </defs>
+            if rule.style.trim().is_empty() && rule.symbol.is_none() && rule.label.is_none() {
+                bail!("line {}: empty style", line);
+            }
+
+            if rule.size.is_some() && rule.symbol.is_none() {
+                bail!("line {}: a size without a symbol", line);
+            }
+
+            if rule.halo.is_some() && rule.label.is_none() {
+                bail!("line {}: a halo without a label", line);
+            }
+
+            for name in [Some(&rule.style), rule.casing.as_ref()]
+                .into_iter()
+                .flatten()
+                .flat_map(|style| patterns(style))
+            {
+                if !style.has_file(&format!("pattern/{}.svg", name)) {
+                    bail!(
+                        "line {}: unknown pattern {}, add pattern/{}.svg",
+                        line,
+                        name,
+                        name
+                    );
+                }
+            }
+
+            if let Some(class) = &rule.shield {
+                if !style.has_shield(class) {
+                    bail!(
+                        "line {}: no shields for {}, add symbols/shields/{}_1x1.svg",
+                        line,
+                        class,
+                        class
+                    );
+                }
+            }
+
+            let layer = match &rule.layer {
+                Some(name) => style
+                    .layers
+                    .iter()
+                    .position(|layer| layer == name)
+                    .ok_or_else(|| anyhow!("line {}: unknown layer {}", line, name))?,
+                None => 0,
+            };
+
+            let class = style.class_name(&conditions);
+
+            let rule = Rule {
+                conditions,
+                class,
+                layer,
+                z_index: rule.z_index,
+                style: rule.style,
+                symbol: rule.symbol,
+                size: rule.size,
+                label: rule.label,
+                halo: rule.halo,
+                importance: rule.importance,
+                shield: rule.shield,
+                casing: rule.casing,
+                legend: rule.legend,
+            };
+
+            // a rule for ways and a rule for nodes may have the same tags
+            if style.rules.iter().any(|r| {
+                r.is_node() == rule.is_node()
+                    && r.conditions.len() == rule.conditions.len()
+                    && r.conditions.iter().all(|c| rule.conditions.contains(c))
+            }) {
+                bail!("line {}: duplicate rule", line);
+            }
+
+            style.rules.push(rule);
+        }
+
+        Ok(style)
+    }
+
+    /// An svg file of the style, like `symbols/amenity/atm.svg`: next to the
+    /// style file, or else the one of the built in style
+    pub fn svg_file(&self, dir: &str, name: &str) -> Result<SvgFile> {
+        let file = format!("{}/{}.svg", dir, name);
+
+        if let Some(path) = self.dir.as_ref().map(|d| d.join(&file)) {
+            if path.exists() {
+                return SvgFile::read(&path);
+            }
+        }
+
+        let Some(text) = BUILT_IN.get_file(&file).and_then(|f| f.contents_utf8()) else {
+            bail!(
+                "Can not find {} next to the style or in the built in style",
+                file
+            );
+        };
+
+        SvgFile::parse(text).with_context(|| format!("Invalid svg file {}", file))
+    }
 
-pub fn defs_pattern() -> String {
-    let leaftype_broadleaved = include_str!("../style/pattern/leaftype_broadleaved.svg").split("\n").collect::<Vec<&str>>();
+    /// true when the style has the file, next to the style or built in
+    fn has_file(&self, file: &str) -> bool {
+        self.dir.as_ref().is_some_and(|dir| dir.join(file).exists())
+            || BUILT_IN.get_file(file).is_some()
+    }
+
+    /// The letters and lines of the shields of the class, from files like
+    /// `symbols/shields/<class>_3x1.svg` next to the style and built in
+    fn shield_sizes(&self, class: &str) -> BTreeSet<(usize, usize)> {
+        let prefix = format!("{}_", class);
+        let size = |name: &str| -> Option<(usize, usize)> {
+            let (letters, lines) = name
+                .strip_prefix(&prefix)?
+                .strip_suffix(".svg")?
+                .split_once('x')?;
+            Some((letters.parse().ok()?, lines.parse().ok()?))
+        };
+
+        let mut sizes = BTreeSet::new();
+
+        if let Some(entries) = self
+            .dir
+            .as_ref()
+            .and_then(|dir| std::fs::read_dir(dir.join("symbols/shields")).ok())
+        {
+            sizes.extend(
+                entries
+                    .flatten()
+                    .filter_map(|entry| size(&entry.file_name().to_string_lossy())),
+            );
+        }
+
+        if let Some(dir) = BUILT_IN.get_dir("symbols/shields") {
+            sizes.extend(
+                dir.files()
+                    .filter_map(|file| size(&file.path().file_name()?.to_string_lossy())),
+            );
+        }
+
+        sizes
+    }
+
+    /// true when there is a shield of the class of any size
+    fn has_shield(&self, class: &str) -> bool {
+        !self.shield_sizes(class).is_empty()
+    }
+
+    /// The symbol of the smallest shield of the class with room for the
+    /// letters on the lines, like `shields/motorway_3x1`. None when no
+    /// shield is large enough.
+    pub fn shield(&self, class: &str, letters: usize, lines: usize) -> Option<String> {
+        self.shield_sizes(class)
+            .into_iter()
+            .filter(|&(l, n)| l >= letters && n >= lines)
+            .min_by_key(|&(l, n)| (n, l))
+            .map(|(l, n)| format!("shields/{}_{}x{}", class, l, n))
+    }
+
+    /// The most specific matching rule of `rules`
+    fn find<'a>(
+        rules: impl Iterator<Item = (usize, &'a Rule)>,
+        tags: &[(&str, &str)],
+    ) -> Option<(usize, &'a Rule)> {
+        let mut found: Option<(usize, &Rule)> = None;
+
+        for (i, rule) in rules.filter(|(_, r)| r.matches(tags)) {
+            if found.is_none_or(|(_, f)| rule.specificity() > f.specificity()) {
+                found = Some((i, rule));
+            }
+        }
+
+        found
+    }
+
+    /// A css class for a rule made of its conditions, joined with `_`. A
+    /// class already in use, by a rule with the same tags for nodes, gets a
+    /// number.
+    fn class_name(&self, conditions: &[Condition]) -> String {
+        let name: Vec<String> = conditions.iter().map(|c| c.class_name()).collect();
+        let name = name.join("_");
+
+        let name = match name.chars().next() {
+            Some(c) if c.is_ascii_alphabetic() => name,
+            _ => format!("rule-{}", name),
+        };
+
+        let taken = |class: &str| self.rules.iter().any(|rule| rule.class == class);
+
+        let mut class = name.clone();
+        let mut n = 1;
+        while taken(&class) {
+            n += 1;
+            class = format!("{}-{}", name, n);
+        }
 
-    let mut defs = "<defs>".to_string();
-    defs += r#"<pattern width="256" height="256" patternUnits="userSpaceOnUse" id="leaftype_broadleaved">"#;
-    defs += &leaftype_broadleaved[2..(leaftype_broadleaved.len()-2)].join("\n");
-    defs += "";
-    defs += "</pattern>";
-    defs += "</defs>";
-    defs
-}
\ No newline at end of file
+        class
+    }
+
+    /// A `<style>` with a css class for every style of the rules: the style,
+    /// the casing and the label. Then the grid and the shield text, and the
+    /// furniture, legend and atlas sized for `mm` metres on a millimetre of
+    /// paper, and the preview of the style.
+    pub fn css(&self, mm: f64) -> String {
+        let mut css = "<style>\n".to_string();
+
+        for rule in &self.rules {
+            if !rule.style.trim().is_empty() {
+                css += &format!(".{} {{ {} }}\n", rule.class, rule.style);
+            }
+
+            if let Some(casing) = &rule.casing {
+                css += &format!(".{}-casing {{ {} }}\n", rule.class, casing);
+            }
+
+            if let Some(label) = rule.label() {
+                css += &format!(
+                    ".{}-label {{ {} }}\n",
+                    rule.class,
+                    label::text_style(&label)
+                );
+            }
+        }
+
+        let grid = &self.grid;
+        css += &format!(".grid {{ {} }}\n", grid.style);
+        css += &format!(".grid-label {{ {} }}\n", grid.label);
+        css += &format!(".graticule {{ {} }}\n", grid.graticule_style);
+        css += &format!(".graticule-label {{ {} }}\n", grid.graticule_label);
+
+        css += &format!(".shield-text {{ {} }}\n", shield::TEXT_STYLE);
+
+        css += &furniture::css(mm);
+        css += &legend::css(mm);
+        css += &atlas::css(mm);
+        css += &preview::css();
+
+        css + "</style>"
+    }
+
+    /// The patterns the fill and stroke in the style and casing of the rules
+    /// refer to with `url(#name)`
+    pub fn patterns(&self, rules: &BTreeSet<usize>) -> BTreeSet<String> {
+        rules
+            .iter()
+            .filter_map(|&i| self.rules.get(i))
+            .flat_map(|rule| [Some(&rule.style), rule.casing.as_ref()])
+            .flatten()
+            .flat_map(|style| patterns(style))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// The legend of the rules in the order of the style. Rules with the
+    /// same caption are shown once, rules with an empty caption not at all.
+    pub fn legend(&self, rules: &BTreeSet<usize>) -> Vec<LegendEntry<'_>> {
+        let mut entries: Vec<LegendEntry> = Vec::new();
+
+        for rule in rules.iter().filter_map(|&i| self.rules.get(i)) {
+            let entry = rule.legend_entry();
+
+            if entry.caption.is_empty() || entries.iter().any(|e| e.caption == entry.caption) {
+                continue;
+            }
+
+            entries.push(entry);
+        }
+
+        entries
+    }
+
+    /// Every rule in the order of the style, for a preview of the style
+    pub fn entries(&self) -> Vec<LegendEntry<'_>> {
+        self.rules.iter().map(|rule| rule.legend_entry()).collect()
+    }
+
+    /// The symbols of all rules
+    pub fn symbols(&self) -> BTreeSet<String> {
+        self.rules
+            .iter()
+            .filter_map(|rule| rule.symbol.clone())
+            .collect()
+    }
+
+    /// The groups of the svg in drawing order
+    pub fn layers(&self) -> &[String] {
+        &self.layers
+    }
+
+    /// The index of a layer group, like [GRID] or [LABELS]
+    pub fn layer(&self, name: &str) -> usize {
+        self.layers
+            .iter()
+            .position(|layer| layer == name)
+            .unwrap_or(0)
+    }
+
+    /// The most specific rule for ways and relations that matches the tags,
+    /// when more rules are equally specific the first one in the file wins
+    pub fn way(&self, tags: &[(&str, &str)]) -> Option<WayStyle<'_>> {
+        let rules = self.rules.iter().enumerate().filter(|(_, r)| !r.is_node());
+
+        Style::find(rules, tags).map(|(i, rule)| WayStyle {
+            rule: i,
+            class: &rule.class,
+            key: rule.key(tags),
+            layer: rule.layer,
+            z_index: rule.z_index,
+            style: &rule.style,
+            casing: rule.casing.as_deref(),
+            label: rule.label(),
+            shield: rule.shield.as_deref(),
+        })
+    }
+
+    /// The symbol and label of a node, from the most specific node rule
+    pub fn node(&self, tags: &[(&str, &str)]) -> Option<NodeStyle<'_>> {
+        let rules = self.rules.iter().enumerate().filter(|(_, r)| r.is_node());
+
+        Style::find(rules, tags).map(|(i, rule)| NodeStyle {
+            rule: i,
+            class: if rule.style.trim().is_empty() {
+                ""
+            } else {
+                &rule.class
+            },
+            key: rule.key(tags),
+            layer: rule.layer,
+            z_index: rule.z_index,
+            symbol: rule.symbol.as_deref(),
+            size: rule.size,
+            label: rule.label(),
+        })
+    }
+}
+
+/// true when the svg style fills the shape
+pub fn is_filled(style: &str) -> bool {
+    style
+        .split(';')
+        .filter_map(|declaration| declaration.split_once(':'))
+        .find(|(property, _)| property.trim() == "fill")
+        .is_none_or(|(_, value)| value.trim() != "none")
+}
+
+/// The font-size of a text style in svg units, 16 when it is missing
+pub fn font_size(style: &str) -> f64 {
+    style
+        .split(';')
+        .filter_map(|declaration| declaration.split_once(':'))
+        .find(|(property, _)| property.trim() == "font-size")
+        .and_then(|(_, value)| value.trim().trim_end_matches("px").parse().ok())
+        .unwrap_or(16.0)
+}
+
+/// The patterns the fill and stroke of an svg style refer to with
+/// `url(#name)`
+fn patterns(style: &str) -> impl Iterator<Item = &str> {
+    style
+        .split(';')
+        .filter_map(|declaration| declaration.split_once(':'))
+        .filter(|(property, _)| matches!(property.trim(), "fill" | "stroke"))
+        .filter_map(|(_, value)| value.trim().strip_prefix("url(#"))
+        .filter_map(|reference| reference.split_once(')'))
+        .map(|(name, _)| name.trim())
+}
+
+/// 1-based line number of a byte offset in the text
+fn line_number(text: &str, offset: usize) -> usize {
+    text[..offset].matches('\n').count() + 1
+}
@@ -0,0 +1,127 @@
+use crate::escape;
+use anyhow::{bail, Context, Result};
+use roxmltree::{Node, NodeType};
+use std::path::Path;
+
+const SVG_NS: &str = "http://www.w3.org/2000/svg";
+const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
+
+/// An svg file of the style directory: the size of the drawing and the
+/// elements in it, ready to be put in a `<symbol>` or `<pattern>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgFile {
+    pub width: f64,
+    pub height: f64,
+    pub view_box: String,
+    pub content: String,
+}
+
+impl SvgFile {
+    pub fn read(path: &Path) -> Result<SvgFile> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Can not read {}", path.display()))?;
+
+        SvgFile::parse(&text).with_context(|| format!("Invalid svg file {}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> Result<SvgFile> {
+        let document = roxmltree::Document::parse(text)?;
+        let root = document.root_element();
+
+        if root.tag_name().name() != "svg" {
+            bail!("the root element is not an svg");
+        }
+
+        let length = |name: &str| -> Result<Option<f64>> {
+            root.attribute(name)
+                .map(|value| {
+                    value
+                        .trim()
+                        .trim_end_matches("px")
+                        .parse()
+                        .with_context(|| format!("invalid {} '{}'", name, value))
+                })
+                .transpose()
+        };
+
+        let view_box: Option<Vec<f64>> = root.attribute("viewBox").map(|view_box| {
+            view_box
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter_map(|n| n.parse().ok())
+                .collect()
+        });
+
+        let (width, height) = match (length("width")?, length("height")?, &view_box) {
+            (Some(width), Some(height), _) => (width, height),
+            (_, _, Some(view_box)) if view_box.len() == 4 => (view_box[2], view_box[3]),
+            _ => bail!("the svg needs a width and height or a viewBox"),
+        };
+
+        let view_box = match root.attribute("viewBox") {
+            Some(view_box) => view_box.to_string(),
+            None => format!("0 0 {} {}", width, height),
+        };
+
+        let mut content = String::new();
+        for child in root.children() {
+            write_node(child, &mut content);
+        }
+
+        Ok(SvgFile {
+            width,
+            height,
+            view_box,
+            content: content.trim().to_string(),
+        })
+    }
+
+    /// The content with `{prefix}-` before every id and every reference to
+    /// an id, so the ids of one file do not clash with the rest of the map
+    pub fn content_with_ids(&self, prefix: &str) -> String {
+        self.content
+            .replace(" id=\"", &format!(" id=\"{}-", prefix))
+            .replace("href=\"#", &format!("href=\"#{}-", prefix))
+            .replace("url(#", &format!("url(#{}-", prefix))
+    }
+}
+
+/// The svg of a node again, without the elements and attributes of editors
+/// like Inkscape, the metadata and the comments
+fn write_node(node: Node, out: &mut String) {
+    match node.node_type() {
+        NodeType::Text => *out += &escape(node.text().unwrap_or_default()),
+        NodeType::Element => {
+            let name = node.tag_name();
+            if name.namespace().is_some_and(|ns| ns != SVG_NS) || name.name() == "metadata" {
+                return;
+            }
+
+            *out += &format!("<{}", name.name());
+
+            for attribute in node.attributes() {
+                let prefix = match attribute.namespace() {
+                    None => "",
+                    Some(XLINK_NS) => "xlink:",
+                    Some(_) => continue,
+                };
+                *out += &format!(
+                    " {}{}=\"{}\"",
+                    prefix,
+                    attribute.name(),
+                    escape(attribute.value())
+                );
+            }
+
+            if node.has_children() {
+                *out += ">";
+                for child in node.children() {
+                    write_node(child, out);
+                }
+                *out += &format!("</{}>", name.name());
+            } else {
+                *out += "/>";
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,104 @@
+use crate::geometry::{self, Point, Rect};
+use crate::style::Style;
+use anyhow::Result;
+use std::collections::BTreeSet;
+
+/// A node drawn with a symbol of `style/symbols`
+#[derive(Debug, Clone)]
+pub struct Poi {
+    pub id: i64,
+    pub point: Point,
+    /// index of the layer group
+    pub layer: usize,
+    pub z_index: u32,
+    /// name of the symbol, the path below `symbols/` without `.svg`
+    pub symbol: String,
+    /// width and height in metres
+    pub size: f64,
+    /// css class of the style, empty without a style
+    pub class: String,
+    /// the data attributes of the matched tag
+    pub data: String,
+    pub tags: String,
+}
+
+impl Poi {
+    /// `<use>` of the symbol centered on the node
+    pub fn svg(&self, precision: u32) -> String {
+        let round = |v: f64| geometry::round(v, precision);
+
+        let mut line = format!(
+            "<use xlink:href=\"#{}\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" id=\"n{}\"",
+            symbol_id(&self.symbol),
+            round(self.point.0 - self.size / 2.0),
+            round(-self.point.1 - self.size / 2.0),
+            self.size,
+            self.size,
+            self.id
+        );
+
+        if !self.class.is_empty() {
+            line += &format!(" class=\"{}\"", self.class);
+        }
+
+        line + &format!("{}><desc>{}</desc></use>", self.data, self.tags)
+    }
+}
+
+pub struct Pois(Vec<Poi>);
+
+impl Pois {
+    pub fn new() -> Pois {
+        Pois(Vec::new())
+    }
+
+    pub fn one(poi: Poi) -> Pois {
+        Pois(vec![poi])
+    }
+
+    pub fn combine(mut self, other: Pois) -> Pois {
+        self.0.extend(other.0);
+        self
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Poi> {
+        self.0.iter()
+    }
+
+    /// The room the symbols take
+    pub fn boxes(&self) -> Vec<Rect> {
+        self.0
+            .iter()
+            .map(|poi| Rect::around(poi.point, poi.size, poi.size))
+            .collect()
+    }
+}
+
+/// `<defs>` with a `<symbol>` for every symbol used, each one only once
+pub fn defs(names: &BTreeSet<String>, style: &Style) -> Result<String> {
+    if names.is_empty() {
+        return Ok(String::new());
+    }
+
+    let mut defs = "<defs>\n".to_string();
+
+    for name in names {
+        let file = style.svg_file("symbols", name)?;
+        let id = symbol_id(name);
+
+        // the ids in the icons must be unique in the map
+        defs += &format!(
+            "<symbol id=\"{}\" viewBox=\"{}\">\n{}\n</symbol>\n",
+            id,
+            file.view_box,
+            file.content_with_ids(&id)
+        );
+    }
+
+    Ok(defs + "</defs>")
+}
+
+/// `amenity/atm` becomes `symbol-amenity-atm`
+pub fn symbol_id(name: &str) -> String {
+    format!("symbol-{}", name.replace(['/', ' '], "-"))
+}
@@ -0,0 +1,243 @@
+//! Write small osm.pbf fixtures for the tests.
+//!
+//! Only the parts of the format the converter reads are written: raw
+//! (uncompressed) blobs, one primitive block, plain or dense nodes, ways and
+//! relations.
+
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+type Tags = Vec<(String, String)>;
+type Members = Vec<(Member, String)>;
+
+pub enum Member {
+    Node(i64),
+    Way(i64),
+    Relation(i64),
+}
+
+#[derive(Default)]
+pub struct Pbf {
+    dense: bool,
+    nodes: Vec<(i64, f64, f64, Tags)>,
+    ways: Vec<(i64, Vec<i64>, Tags)>,
+    relations: Vec<(i64, Members, Tags)>,
+}
+
+fn tags(tags: &[(&str, &str)]) -> Tags {
+    tags.iter()
+        .map(|&(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+impl Pbf {
+    pub fn new() -> Pbf {
+        Pbf::default()
+    }
+
+    /// Write the nodes as `DenseNodes` instead of plain `Node` messages
+    pub fn dense(mut self, dense: bool) -> Pbf {
+        self.dense = dense;
+        self
+    }
+
+    pub fn node(mut self, id: i64, lat: f64, lon: f64, t: &[(&str, &str)]) -> Pbf {
+        self.nodes.push((id, lat, lon, tags(t)));
+        self
+    }
+
+    pub fn way(mut self, id: i64, refs: &[i64], t: &[(&str, &str)]) -> Pbf {
+        self.ways.push((id, refs.to_vec(), tags(t)));
+        self
+    }
+
+    pub fn relation(mut self, id: i64, members: Vec<(Member, &str)>, t: &[(&str, &str)]) -> Pbf {
+        let members = members
+            .into_iter()
+            .map(|(m, role)| (m, role.to_string()))
+            .collect();
+        self.relations.push((id, members, tags(t)));
+        self
+    }
+
+    /// Write the file in the temp dir and return the path
+    pub fn write(&self, name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("osm-bpf-to-svg-{}.osm.pbf", name));
+
+        let mut header = Vec::new();
+        bytes(&mut header, 4, b"OsmSchema-V0.6");
+
+        let mut out = Vec::new();
+        blob(&mut out, "OSMHeader", &header);
+        blob(&mut out, "OSMData", &self.primitive_block());
+
+        std::fs::write(&path, out).unwrap();
+        path
+    }
+
+    fn primitive_block(&self) -> Vec<u8> {
+        let mut strings = StringTable(vec![String::new()]);
+
+        let mut group = Vec::new();
+
+        if self.dense {
+            let mut dense = Vec::new();
+            let (mut id, mut lat, mut lon) = (0, 0, 0);
+            let mut ids = Vec::new();
+            let mut lats = Vec::new();
+            let mut lons = Vec::new();
+            let mut keys_vals = Vec::new();
+            for (node_id, node_lat, node_lon, t) in &self.nodes {
+                let (node_lat, node_lon) = (coord(*node_lat), coord(*node_lon));
+                ids.push(node_id - id);
+                lats.push(node_lat - lat);
+                lons.push(node_lon - lon);
+                (id, lat, lon) = (*node_id, node_lat, node_lon);
+                for (k, v) in t {
+                    keys_vals.push(strings.index(k));
+                    keys_vals.push(strings.index(v));
+                }
+                keys_vals.push(0);
+            }
+            packed_sint(&mut dense, 1, &ids);
+            packed_sint(&mut dense, 8, &lats);
+            packed_sint(&mut dense, 9, &lons);
+            packed(&mut dense, 10, &keys_vals);
+            bytes(&mut group, 2, &dense);
+        } else {
+            for (id, lat, lon, t) in &self.nodes {
+                let mut node = Vec::new();
+                varint_field(&mut node, 1, zigzag(*id));
+                key_vals(&mut node, &mut strings, t);
+                varint_field(&mut node, 8, zigzag(coord(*lat)));
+                varint_field(&mut node, 9, zigzag(coord(*lon)));
+                bytes(&mut group, 1, &node);
+            }
+        }
+
+        for (id, refs, t) in &self.ways {
+            let mut way = Vec::new();
+            varint_field(&mut way, 1, *id as u64);
+            key_vals(&mut way, &mut strings, t);
+            packed_sint(&mut way, 8, &delta(refs));
+            bytes(&mut group, 3, &way);
+        }
+
+        for (id, members, t) in &self.relations {
+            let mut relation = Vec::new();
+            varint_field(&mut relation, 1, *id as u64);
+            key_vals(&mut relation, &mut strings, t);
+            let roles: Vec<u64> = members.iter().map(|(_, r)| strings.index(r)).collect();
+            let (ids, types): (Vec<i64>, Vec<u64>) = members
+                .iter()
+                .map(|(m, _)| match m {
+                    Member::Node(id) => (*id, 0),
+                    Member::Way(id) => (*id, 1),
+                    Member::Relation(id) => (*id, 2),
+                })
+                .unzip();
+            packed(&mut relation, 8, &roles);
+            packed_sint(&mut relation, 9, &delta(&ids));
+            packed(&mut relation, 10, &types);
+            bytes(&mut group, 4, &relation);
+        }
+
+        let mut table = Vec::new();
+        for s in &strings.0 {
+            bytes(&mut table, 1, s.as_bytes());
+        }
+
+        let mut block = Vec::new();
+        bytes(&mut block, 1, &table);
+        bytes(&mut block, 2, &group);
+        block
+    }
+}
+
+struct StringTable(Vec<String>);
+
+impl StringTable {
+    fn index(&mut self, s: &str) -> u64 {
+        if let Some(i) = self.0.iter().position(|e| e == s) {
+            i as u64
+        } else {
+            self.0.push(s.to_string());
+            (self.0.len() - 1) as u64
+        }
+    }
+}
+
+/// degrees to the default granularity of 100 nanodegrees
+fn coord(degrees: f64) -> i64 {
+    (degrees * 1e7).round() as i64
+}
+
+fn delta(values: &[i64]) -> Vec<i64> {
+    let mut last = 0;
+    values
+        .iter()
+        .map(|&v| {
+            let d = v - last;
+            last = v;
+            d
+        })
+        .collect()
+}
+
+fn zigzag(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
+
+fn varint(out: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        out.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+fn varint_field(out: &mut Vec<u8>, field: u64, v: u64) {
+    varint(out, field << 3);
+    varint(out, v);
+}
+
+fn bytes(out: &mut Vec<u8>, field: u64, b: &[u8]) {
+    varint(out, (field << 3) | 2);
+    varint(out, b.len() as u64);
+    out.extend_from_slice(b);
+}
+
+fn packed(out: &mut Vec<u8>, field: u64, values: &[u64]) {
+    let mut b = Vec::new();
+    for &v in values {
+        varint(&mut b, v);
+    }
+    bytes(out, field, &b);
+}
+
+fn packed_sint(out: &mut Vec<u8>, field: u64, values: &[i64]) {
+    let values: Vec<u64> = values.iter().map(|&v| zigzag(v)).collect();
+    packed(out, field, &values);
+}
+
+fn key_vals(out: &mut Vec<u8>, strings: &mut StringTable, t: &Tags) {
+    let keys: Vec<u64> = t.iter().map(|(k, _)| strings.index(k)).collect();
+    let vals: Vec<u64> = t.iter().map(|(_, v)| strings.index(v)).collect();
+    packed(out, 2, &keys);
+    packed(out, 3, &vals);
+}
+
+fn blob(out: &mut Vec<u8>, blob_type: &str, data: &[u8]) {
+    let mut blob = Vec::new();
+    bytes(&mut blob, 1, data);
+    varint_field(&mut blob, 2, data.len() as u64);
+
+    let mut header = Vec::new();
+    bytes(&mut header, 1, blob_type.as_bytes());
+    varint_field(&mut header, 3, blob.len() as u64);
+
+    out.extend_from_slice(&(header.len() as u32).to_be_bytes());
+    out.extend_from_slice(&header);
+    out.extend_from_slice(&blob);
+}
@@ -1,4 +1,7 @@
+mod common;
+
 use anyhow::Result;
+use common::{Member, Pbf};
 use osm_bpf_to_svg::*;
 
 #[test]
@@ -15,3 +18,1332 @@ fn test_10_by_10_km() -> Result<()> {
 
     Ok(())
 }
+
+/// A small square building around lat 52.0 lon 5.5
+fn building(dense: bool) -> Pbf {
+    Pbf::new()
+        .dense(dense)
+        .node(1, 52.0, 5.5, &[])
+        .node(2, 52.0, 5.501, &[])
+        .node(3, 52.001, 5.501, &[])
+        .node(4, 52.001, 5.5, &[])
+        .way(10, &[1, 2, 3, 4, 1], &[("building", "yes")])
+}
+
+/// 1 by 1 km around lat 52.0 lon 5.5
+fn select_box() -> SelectBox {
+    let (x, y) = rijksdriehoek::wgs84_to_rijksdriehoek(52.0, 5.5);
+    SelectBox::new(x as i64 - 500, y as i64 - 500, 1_000, 1_000)
+}
+
+fn render(pbf: &Pbf, name: &str) -> Result<String> {
+    render_with_options(pbf, name, &Options::default())
+}
+
+fn render_with_style(pbf: &Pbf, name: &str, style: Option<&str>) -> Result<String> {
+    let style_path = style.map(|style| {
+        let path = std::env::temp_dir().join(format!("osm-bpf-to-svg-{}.toml", name));
+        std::fs::write(&path, style).unwrap();
+        path.to_string_lossy().to_string()
+    });
+
+    let output = std::env::temp_dir().join(format!("osm-bpf-to-svg-{}.svg", name));
+
+    osm_bpf_to_svg::doit(
+        select_box(),
+        pbf.write(name).to_string_lossy().to_string(),
+        output.to_string_lossy().to_string(),
+        style_path,
+    )?;
+
+    Ok(std::fs::read_to_string(output)?)
+}
+
+fn render_with_options(pbf: &Pbf, name: &str, options: &Options) -> Result<String> {
+    render_box(pbf, name, select_box(), options)
+}
+
+fn render_box(pbf: &Pbf, name: &str, select_box: SelectBox, options: &Options) -> Result<String> {
+    let output = std::env::temp_dir().join(format!("osm-bpf-to-svg-{}.svg", name));
+
+    osm_bpf_to_svg::doit_with_options(
+        select_box,
+        pbf.write(name).to_string_lossy().to_string(),
+        output.to_string_lossy().to_string(),
+        options,
+    )?;
+
+    Ok(std::fs::read_to_string(output)?)
+}
+
+/// The absolute points in the path d of the element with the id, y is
+/// flipped back and a closed ring ends with the first point
+fn path_points(svg: &str, id: &str) -> Vec<(f64, f64)> {
+    let element = &svg[svg.find(&format!(r#"id="{}""#, id)).unwrap()..];
+    let element = &svg[svg[..svg.len() - element.len()].rfind('<').unwrap()..];
+    let d = element.split(r#" d=""#).nth(1).unwrap();
+    let d = &d[..d.find('"').unwrap()];
+
+    // M x y l dx dy dx dy ... z
+    let mut points = Vec::new();
+    let mut relative = false;
+    let mut numbers = Vec::new();
+    for token in d.split_whitespace() {
+        match token {
+            "M" => relative = false,
+            "l" => relative = true,
+            "z" => points.push(points[0]),
+            _ => numbers.push(token.parse::<f64>().unwrap()),
+        }
+        if numbers.len() == 2 {
+            let (mut x, mut y) = (numbers[0], -numbers[1]);
+            if relative {
+                let &(last_x, last_y) = points.last().unwrap();
+                (x, y) = (last_x + x, last_y + y);
+            }
+            points.push((x, y));
+            numbers.clear();
+        }
+    }
+
+    points
+}
+
+/// The first position of the bytes
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[test]
+fn test_multipolygon() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let pbf = Pbf::new()
+        // the outer ring in two open halves, the second one backwards
+        .node(1, 51.998, 5.497, &[])
+        .node(2, 51.998, 5.503, &[])
+        .node(3, 52.002, 5.503, &[])
+        .node(4, 52.002, 5.497, &[])
+        .way(20, &[1, 2, 3], &[])
+        .way(21, &[1, 4, 3], &[])
+        // the inner ring
+        .node(5, 51.999, 5.499, &[])
+        .node(6, 51.999, 5.501, &[])
+        .node(7, 52.001, 5.501, &[])
+        .node(8, 52.001, 5.499, &[])
+        .way(22, &[5, 6, 7, 8, 5], &[])
+        .relation(
+            30,
+            vec![
+                (Member::Way(20), "outer"),
+                (Member::Way(21), "outer"),
+                (Member::Way(22), "inner"),
+            ],
+            &[("type", "multipolygon"), ("landuse", "forest")],
+        )
+        // a ring that does not close and a way that is not in the file
+        .node(9, 51.9985, 5.4985, &[])
+        .node(10, 51.9985, 5.5015, &[])
+        .way(23, &[9, 10], &[])
+        .relation(
+            31,
+            vec![(Member::Way(23), "outer"), (Member::Way(99), "outer")],
+            &[("type", "multipolygon"), ("landuse", "grass")],
+        );
+
+    let svg = render(&pbf, "multipolygon")?;
+
+    assert_eq!(svg.matches(r#"id="r30""#).count(), 1);
+    let path = svg
+        .lines()
+        .find(|line| line.contains(r#"id="r30""#))
+        .unwrap();
+    assert!(path.contains(r#"fill-rule="evenodd""#));
+    let d = &path[path.find(" d=\"").unwrap() + 4..];
+    let d = &d[..d.find('"').unwrap()];
+    assert_eq!(d.matches(['M', 'm']).count(), 2, "{}", d);
+
+    assert!(!svg.contains(r#"id="r31""#));
+
+    Ok(())
+}
+
+#[test]
+fn test_plain_nodes() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let plain = render(&building(false), "plain-nodes")?;
+    let dense = render(&building(true), "dense-nodes")?;
+
+    assert!(plain.contains(r#"id="10""#));
+    assert!(!plain.contains("M 0 -0"));
+    assert_eq!(plain, dense);
+
+    Ok(())
+}
+
+#[test]
+fn test_style_file() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let style = r#"
+[[rule]]
+key = "building"
+prio = 10
+style = "fill:red"
+"#;
+    let svg = render_with_style(&building(false), "style-file", Some(style))?;
+    assert!(svg.contains(".building { fill:red }"));
+    assert!(svg.contains(r#"id="10" class="building" data-key="building" data-value="yes""#));
+
+    let style = r#"
+[[rule]]
+key = "building"
+prio = "high"
+style = "fill:red"
+"#;
+    let err = render_with_style(&building(false), "style-file-error", Some(style)).unwrap_err();
+    assert!(format!("{:?}", err).contains("line 4"));
+
+    Ok(())
+}
+
+#[test]
+fn test_tag_combination() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let pbf = building(false).way(
+        11,
+        &[1, 2, 3, 4, 1],
+        &[("building", "yes"), ("building:levels", "5")],
+    );
+
+    let style = r#"
+[[rule]]
+tags = ["building=*", "building:levels>3"]
+prio = 10
+style = "fill:red"
+
+[[rule]]
+key = "building"
+prio = 10
+style = "fill:blue"
+"#;
+    let svg = render_with_style(&pbf, "tag-combination", Some(style))?;
+    assert!(svg.contains(".building_building-levels-gt-3 { fill:red }"));
+    assert!(svg.contains(".building { fill:blue }"));
+    assert!(svg.contains(r#"id="10" class="building""#));
+    assert!(svg.contains(r#"id="11" class="building_building-levels-gt-3""#));
+
+    Ok(())
+}
+
+#[test]
+fn test_clip() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    // a road from the center of the box 7 km to the east
+    let pbf = Pbf::new()
+        .node(1, 52.0, 5.5, &[])
+        .node(2, 52.0, 5.6, &[])
+        .way(10, &[1, 2], &[("highway", "primary")]);
+
+    let b = select_box();
+
+    let svg = render(&pbf, "clip")?;
+    let points = path_points(&svg, "10");
+    assert_eq!(points.len(), 2);
+    assert_eq!(points[1].0, b.x_max() as f64);
+
+    let options = Options {
+        margin: 100,
+        ..Options::default()
+    };
+    let svg = render_with_options(&pbf, "clip-margin", &options)?;
+    let points = path_points(&svg, "10");
+    assert_eq!(points[1].0, b.x_max() as f64 + 100.0);
+
+    // a forest polygon sticking out of the box stays a closed ring
+    let pbf =
+        building(false)
+            .node(5, 52.0, 5.6, &[])
+            .way(11, &[1, 5, 3, 1], &[("landuse", "forest")]);
+    let svg = render(&pbf, "clip-polygon")?;
+    let points = path_points(&svg, "11");
+    assert_eq!(points.first(), points.last());
+    assert!(points.iter().all(|&(x, _)| x <= b.x_max() as f64));
+
+    Ok(())
+}
+
+#[test]
+fn test_select_crossing() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let pbf = Pbf::new()
+        // a tree row crossing the box without a node inside
+        .node(1, 52.0, 5.4, &[])
+        .node(2, 52.0, 5.6, &[])
+        .way(10, &[1, 2], &[("natural", "tree_row")])
+        // a forest covering the whole box
+        .node(3, 51.9, 5.4, &[])
+        .node(4, 51.9, 5.6, &[])
+        .node(5, 52.1, 5.6, &[])
+        .node(6, 52.1, 5.4, &[])
+        .way(11, &[3, 4, 5, 6, 3], &[("landuse", "forest")])
+        // a fence around the box is not visible in the box
+        .way(12, &[3, 4, 5, 6, 3], &[("barrier", "fence")]);
+
+    let svg = render(&pbf, "select-crossing")?;
+    assert!(svg.contains(r#"id="10""#));
+    assert!(svg.contains(r#"id="11""#));
+    assert!(!svg.contains(r#"id="12""#));
+
+    let b = select_box();
+    let points = path_points(&svg, "11");
+    assert!(points.contains(&(b.x_min() as f64, b.y_min() as f64)));
+
+    Ok(())
+}
+
+#[test]
+fn test_simplify() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    // a straight road with a small wiggle and a duplicate node
+    let pbf = Pbf::new()
+        .node(1, 52.0, 5.499, &[])
+        .node(2, 52.000001, 5.4995, &[])
+        .node(3, 52.0, 5.5, &[])
+        .node(4, 52.0, 5.5, &[])
+        .node(5, 52.0, 5.501, &[])
+        .way(10, &[1, 2, 3, 4, 5], &[("highway", "primary")]);
+
+    let options = Options {
+        tolerance: 1.0,
+        ..Options::default()
+    };
+    let svg = render_with_options(&pbf, "simplify", &options)?;
+    assert_eq!(path_points(&svg, "10").len(), 2);
+
+    let none = Options {
+        tolerance: 0.0,
+        ..Options::default()
+    };
+    let svg = render_with_options(&pbf, "simplify-none", &none)?;
+    assert_eq!(path_points(&svg, "10").len(), 4);
+
+    // the small building is kept and closed
+    let svg = render_with_options(&building(false), "simplify-building", &options)?;
+    assert!(svg.contains(" z \""));
+    assert_eq!(path_points(&svg, "10").len(), 5);
+
+    Ok(())
+}
+
+#[test]
+fn test_projection() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    // on the central meridian of utm zone 31
+    let pbf = Pbf::new()
+        .node(1, 52.0, 3.0, &[])
+        .node(2, 52.0, 3.001, &[])
+        .node(3, 52.001, 3.001, &[])
+        .node(4, 52.001, 3.0, &[])
+        .way(10, &[1, 2, 3, 4, 1], &[("building", "yes")]);
+
+    let options = Options {
+        projection: "utm".parse()?,
+        ..Options::default()
+    };
+    let select_box = SelectBox::new(499_500, 5_760_500, 1_000, 1_000);
+    let svg = render_box(&pbf, "projection-utm", select_box, &options)?;
+    let (x, y) = path_points(&svg, "10")[0];
+    assert_eq!(x, 500_000.0);
+    assert!((y - 5_761_038.0).abs() <= 1.0, "{}", y);
+
+    // x of web mercator is the longitude on the equator
+    let options = Options {
+        projection: Projection::WebMercator,
+        ..Options::default()
+    };
+    let x = 6_378_137.0 * 3.0_f64.to_radians();
+    let y = 6_378_137.0
+        * (std::f64::consts::FRAC_PI_4 + 52.0_f64.to_radians() / 2.0)
+            .tan()
+            .ln();
+    let select_box = SelectBox::new(x as i64 - 500, y as i64 - 500, 1_000, 1_000);
+    let svg = render_box(&pbf, "projection-mercator", select_box, &options)?;
+    // to the decimal of the default precision
+    let (px, py) = path_points(&svg, "10")[0];
+    assert!(
+        (px - x).abs() <= 0.05 && (py - y).abs() <= 0.05,
+        "{} {}",
+        px,
+        py
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_precision() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let options = Options {
+        precision: 2,
+        ..Options::default()
+    };
+    let svg = render_with_options(&building(false), "precision", &options)?;
+
+    let (x, y) = rijksdriehoek::wgs84_to_rijksdriehoek(52.001, 5.501);
+    let points = path_points(&svg, "10");
+    assert!(points
+        .iter()
+        .any(|&(px, py)| (px - x).abs() < 0.011 && (py - y).abs() < 0.011));
+
+    Ok(())
+}
+
+#[test]
+fn test_node_store() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    // the nodes are not sorted by id
+    let pbf = Pbf::new()
+        .node(4, 52.001, 5.5, &[])
+        .node(3, 52.001, 5.501, &[])
+        .node(1, 52.0, 5.5, &[])
+        .node(2, 52.0, 5.501, &[])
+        .way(10, &[1, 2, 3, 4, 1], &[("building", "yes")])
+        .way(11, &[1, 3, 5], &[("highway", "primary")]);
+
+    let memory = render(&pbf, "node-store-memory")?;
+    assert!(memory.contains(r#"id="10""#));
+
+    for store in ["sorted", "mmap"] {
+        let options = Options {
+            node_store: store.parse()?,
+            ..Options::default()
+        };
+        let svg = render_with_options(&pbf, &format!("node-store-{}", store), &options)?;
+        assert_eq!(svg, memory, "{}", store);
+    }
+
+    // a file without nodes
+    let options = Options {
+        node_store: NodeStore::Mmap,
+        ..Options::default()
+    };
+    render_with_options(&Pbf::new(), "node-store-empty", &options)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_symbols() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let pbf = building(false)
+        .node(5, 52.0005, 5.5005, &[("amenity", "atm")])
+        .node(6, 52.0006, 5.5006, &[("amenity", "atm")])
+        .node(7, 52.0007, 5.5007, &[("amenity", "unknown")])
+        // outside the box
+        .node(8, 52.1, 5.5, &[("amenity", "atm")]);
+
+    let svg = render(&pbf, "symbols")?;
+    assert_eq!(svg.matches(r#"<symbol id="symbol-amenity-atm""#).count(), 1);
+    assert_eq!(svg.matches("<use ").count(), 2);
+    assert!(svg.contains(r##"<use xlink:href="#symbol-amenity-atm""##));
+    assert!(svg.contains(r#"width="14" height="14" id="n5""#));
+    assert!(!svg.contains(r#"id="n7""#));
+    assert!(!svg.contains(r#"id="n8""#));
+
+    let options = Options {
+        symbol_size: 20.0,
+        ..Options::default()
+    };
+    let svg = render_with_options(&pbf, "symbols-size", &options)?;
+    assert!(svg.contains(r#"width="20" height="20" id="n5""#));
+
+    Ok(())
+}
+
+#[test]
+fn test_way_labels() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let pbf = Pbf::new()
+        .node(1, 52.0, 5.499, &[])
+        .node(2, 52.0, 5.501, &[])
+        .node(3, 52.0001, 5.5, &[])
+        .node(4, 52.0001, 5.50005, &[])
+        // drawn from east to west
+        .way(
+            10,
+            &[2, 1],
+            &[("highway", "primary"), ("name", "Dorpsstraat & Co")],
+        )
+        // 3 m long
+        .way(11, &[3, 4], &[("highway", "primary"), ("name", "Kerkpad")])
+        .way(12, &[1, 2], &[("highway", "primary")]);
+
+    let style = r#"
+[[rule]]
+key = "highway"
+prio = 100
+style = "stroke:black"
+label = "font-size:10px"
+halo = "stroke:white"
+"#;
+    let svg = render_with_style(&pbf, "way-labels", Some(style))?;
+
+    assert!(svg.contains(">Dorpsstraat &amp; Co</textPath>"));
+    assert!(svg.contains(
+        ".highway-label { font-size:10px;stroke:white;stroke-linejoin:round;paint-order:stroke }"
+    ));
+    assert!(svg.contains(r#"<text class="highway-label""#));
+    let points = path_points(&svg, "label-10");
+    assert!(points[0].0 < points[1].0);
+
+    assert!(!svg.contains("label-11"));
+    assert!(!svg.contains("label-12"));
+
+    Ok(())
+}
+
+/// x and y of a `<text>` element, y flipped back
+fn text_point(svg: &str, id: &str) -> Option<(f64, f64)> {
+    let element = &svg[svg.find(&format!(r#"id="{}""#, id))?..];
+    let element = &svg[svg[..svg.len() - element.len()].rfind('<')?..];
+    let attribute = |name: &str| -> f64 {
+        let value = element.split(&format!(r#" {}=""#, name)).nth(1).unwrap();
+        value[..value.find('"').unwrap()].parse().unwrap()
+    };
+
+    Some((attribute("x"), -attribute("y")))
+}
+
+#[test]
+fn test_point_and_area_labels() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let pbf = building(false)
+        // a 69 by 111 m named building
+        .way(
+            11,
+            &[1, 2, 3, 4, 1],
+            &[("building", "yes"), ("name", "Huis")],
+        )
+        // a 7 m wide building, too narrow for the name
+        .node(5, 52.0, 5.5001, &[])
+        .node(6, 52.001, 5.5001, &[])
+        .way(
+            12,
+            &[1, 5, 6, 4, 1],
+            &[("building", "yes"), ("name", "Smal")],
+        )
+        // two places on the same spot, the town goes first
+        .node(20, 52.002, 5.497, &[("place", "village"), ("name", "Dorp")])
+        .node(21, 52.002, 5.497, &[("place", "town"), ("name", "Stad")])
+        .node(
+            22,
+            52.002,
+            5.497,
+            &[("place", "hamlet"), ("name", "Gehucht")],
+        )
+        .node(
+            23,
+            52.002,
+            5.497,
+            &[("place", "hamlet"), ("name", "Buurtschap")],
+        )
+        // a cafe with the name next to the symbol
+        .node(
+            30,
+            51.998,
+            5.497,
+            &[("amenity", "cafe"), ("name", "Koffie")],
+        );
+
+    let style = r#"
+[[rule]]
+key = "building"
+prio = 20
+style = "fill:yellow"
+label = "font-size:10px"
+
+[[rule]]
+key = "place"
+value = "town"
+prio = 0
+label = "font-size:20px"
+importance = 90
+
+[[rule]]
+key = "place"
+prio = 0
+label = "font-size:20px"
+importance = 80
+
+[[rule]]
+key = "amenity"
+value = "cafe"
+prio = 200
+symbol = "amenity/cafe"
+size = 10
+label = "font-size:10px"
+"#;
+    let svg = render_with_style(&pbf, "labels", Some(style))?;
+
+    // inside the building, away from the border
+    let (x, y) = text_point(&svg, "label-11").unwrap();
+    let corner = path_points(&svg, "11")[0];
+    assert!(x - corner.0 > 30.0 && y - corner.1 > 30.0, "{} {}", x, y);
+    assert!(text_point(&svg, "label-12").is_none());
+
+    // the town on the spot, the village shifted a line, the hamlets that
+    // collide everywhere are dropped
+    let town = text_point(&svg, "label-n21").unwrap();
+    let village = text_point(&svg, "label-n20").unwrap();
+    assert_eq!(town.0, village.0);
+    assert_eq!((town.1 - village.1).abs(), 20.0);
+    assert!(text_point(&svg, "label-n22").is_some() != text_point(&svg, "label-n23").is_some());
+
+    // right of the symbol, the x of the use is the left side of the symbol
+    let (x, y) = text_point(&svg, "label-n30").unwrap();
+    let (symbol_x, symbol_y) = text_point(&svg, "n30").unwrap();
+    assert!(x - symbol_x > 28.0, "{} {}", x, symbol_x);
+    assert_eq!(symbol_y - 5.0, y);
+
+    Ok(())
+}
+
+#[test]
+fn test_shields() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let pbf = Pbf::new()
+        // 1 km in the box
+        .node(1, 52.0, 5.49, &[])
+        .node(2, 52.0, 5.51, &[])
+        .way(10, &[1, 2], &[("highway", "motorway"), ("ref", "A12")])
+        // the other carriageway
+        .node(3, 52.0002, 5.49, &[])
+        .node(4, 52.0002, 5.51, &[])
+        .way(11, &[4, 3], &[("highway", "motorway"), ("ref", "A12")])
+        .node(5, 52.003, 5.49, &[])
+        .node(6, 52.003, 5.51, &[])
+        .way(12, &[5, 6], &[("highway", "primary"), ("ref", "N325;E35")])
+        .node(7, 51.997, 5.49, &[])
+        .node(8, 51.997, 5.51, &[])
+        .way(
+            13,
+            &[7, 8],
+            &[("highway", "primary"), ("ref", "ABCDEFGHIJKL")],
+        );
+
+    let svg = render(&pbf, "shields")?;
+
+    assert_eq!(svg.matches(r#"id="shield-10-"#).count(), 2);
+    assert!(!svg.contains(r#"id="shield-11-"#));
+    assert_eq!(svg.matches(">A12</text>").count(), 2);
+    assert_eq!(
+        svg.matches(r#"<symbol id="symbol-shields-motorway_3x1""#)
+            .count(),
+        1
+    );
+
+    assert!(svg.contains(r##"xlink:href="#symbol-shields-primary_4x2""##));
+    assert!(svg.contains(
+        r#"class="shield-text" text-anchor="middle" dominant-baseline="central">N325</text>"#
+    ));
+    assert!(svg.contains(".shield-text { font-family:sans-serif;"));
+    assert!(!svg.contains(" style=\""));
+    assert!(svg.contains(">E35</text>"));
+
+    assert!(!svg.contains(r#"id="shield-13-"#));
+
+    let style = r#"
+[[rule]]
+key = "highway"
+value = "motorway"
+z-index = 1
+style = "stroke:red"
+shield = "autobahn"
+"#;
+    let err = render_with_style(&pbf, "shields-error", Some(style)).unwrap_err();
+    assert!(format!("{:?}", err).contains("line 2: no shields for autobahn"));
+
+    // a style with only the smallest shield leaves out the longer refs
+    let dir = tempfile::tempdir()?;
+    std::fs::create_dir_all(dir.path().join("symbols/shields"))?;
+    std::fs::copy(
+        "style/symbols/shields/motorway_1x1.svg",
+        dir.path().join("symbols/shields/bab_1x1.svg"),
+    )?;
+    let style_path = dir.path().join("style.toml");
+    std::fs::write(
+        &style_path,
+        r#"
+[[rule]]
+key = "highway"
+z-index = 1
+style = "stroke:red"
+shield = "bab"
+"#,
+    )?;
+    let output = dir.path().join("shields.svg");
+    osm_bpf_to_svg::doit(
+        select_box(),
+        Pbf::new()
+            .node(1, 52.0, 5.49, &[])
+            .node(2, 52.0, 5.51, &[])
+            .way(10, &[1, 2], &[("highway", "motorway"), ("ref", "A12")])
+            .node(3, 52.003, 5.49, &[])
+            .node(4, 52.003, 5.51, &[])
+            .way(14, &[3, 4], &[("highway", "motorway"), ("ref", "7")])
+            .write("shields-small")
+            .to_string_lossy()
+            .to_string(),
+        output.to_string_lossy().to_string(),
+        Some(style_path.to_string_lossy().to_string()),
+    )?;
+    let svg = std::fs::read_to_string(output)?;
+    assert!(svg.contains(r#"id="shield-14-"#));
+    assert!(!svg.contains(r#"id="shield-10-"#));
+    assert!(svg.contains(r##"xlink:href="#symbol-shields-bab_1x1""##));
+
+    let options = Options {
+        shield_spacing: 0.0,
+        ..Options::default()
+    };
+    let err = render_with_options(&pbf, "shields-spacing", &options).unwrap_err();
+    assert!(format!("{:?}", err).contains("Invalid shield spacing"));
+
+    Ok(())
+}
+
+#[test]
+fn test_casings_and_layers() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let pbf = building(false)
+        .node(5, 52.0005, 5.499, &[])
+        .node(6, 52.0005, 5.502, &[])
+        .node(7, 51.999, 5.5005, &[])
+        .node(8, 52.002, 5.5005, &[])
+        .way(20, &[5, 6], &[("highway", "primary")])
+        .way(21, &[7, 8], &[("highway", "primary")])
+        .way(22, &[5, 6], &[("highway", "primary"), ("bridge", "yes")])
+        .way(23, &[7, 8], &[("highway", "primary"), ("tunnel", "yes")])
+        .way(24, &[7, 8], &[("highway", "primary"), ("layer", "-2")]);
+
+    let style = r#"
+[[rule]]
+key = "highway"
+prio = 100
+style = "stroke:white"
+casing = "stroke:black"
+
+[[rule]]
+key = "building"
+prio = 200
+style = "fill:red"
+"#;
+    let svg = render_with_style(&pbf, "casings", Some(style))?;
+    let at = |id: &str| svg.find(&format!(r#"id="{}""#, id)).unwrap();
+
+    // the casings of the ground level beneath both fills
+    assert!(at("20-casing") < at("21") && at("21-casing") < at("20"));
+    // tunnels beneath the ground level, layer -2 beneath the tunnel
+    assert!(at("24") < at("23-casing") && at("23") < at("20-casing"));
+    // the bridge over the building with a higher prio
+    assert!(at("10") < at("22-casing") && at("22-casing") < at("22"));
+
+    Ok(())
+}
+
+#[test]
+fn test_layer_groups() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let pbf = building(false)
+        .node(5, 52.0005, 5.499, &[])
+        .node(6, 52.0005, 5.502, &[])
+        .way(20, &[5, 6], &[("highway", "primary"), ("name", "Markt")]);
+
+    let svg = render(&pbf, "layer-groups")?;
+    let group = |name: &str| {
+        svg.find(&format!(
+            r#"<g id="{}" inkscape:groupmode="layer" inkscape:label="{}">"#,
+            name, name
+        ))
+        .unwrap()
+    };
+    let at = |id: &str| svg.find(&format!(r#"id="{}""#, id)).unwrap();
+
+    assert!(svg.contains("xmlns:inkscape="));
+    assert!(group("landuse") < group("water") && group("water") < group("buildings"));
+    assert!(group("buildings") < at("10") && at("10") < group("roads"));
+    assert!(group("roads") < at("20") && at("20") < group("symbols"));
+    assert!(group("grid") < group("labels") && group("labels") < at("label-20"));
+
+    // a style puts the rules in its own layers, the z-index orders them within
+    let style = r#"
+layers = ["labels", "roads", "buildings"]
+
+[[rule]]
+key = "highway"
+z-index = 100
+layer = "roads"
+style = "stroke:black"
+label = "font-size:8px"
+
+[[rule]]
+key = "building"
+z-index = 1
+layer = "buildings"
+style = "fill:red"
+"#;
+    let svg = render_with_style(&pbf, "layer-groups-style", Some(style))?;
+    let at = |id: &str| svg.find(&format!(r#"id="{}""#, id)).unwrap();
+
+    assert!(at("label-20") < at("roads") && at("roads") < at("20"));
+    assert!(at("20") < at("buildings") && at("buildings") < at("10"));
+    assert!(at("10") < at("grid"));
+
+    let style = r#"
+[[rule]]
+key = "building"
+z-index = 1
+layer = "roofs"
+style = "fill:red"
+"#;
+    let err = render_with_style(&pbf, "layer-groups-error", Some(style)).unwrap_err();
+    assert!(format!("{:?}", err).contains("line 2: unknown layer roofs"));
+
+    Ok(())
+}
+
+#[test]
+fn test_patterns() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let svg = render(&building(false), "patterns-none")?;
+    assert!(!svg.contains("<pattern"));
+
+    let pbf = building(false)
+        .node(5, 52.0002, 5.4992, &[])
+        .node(6, 52.0002, 5.4998, &[])
+        .node(7, 52.0008, 5.4998, &[])
+        .way(11, &[5, 6, 7, 5], &[("amenity", "parking")]);
+    let svg = render(&pbf, "patterns-parking")?;
+    assert_eq!(svg.matches(r#"<pattern id="parking""#).count(), 1);
+    assert!(svg.contains(r#"width="10" height="6" viewBox="0 0 10 6""#));
+    assert!(!svg.contains(r#"<pattern id="leaftype_broadleaved""#));
+
+    // the pattern is parsed: editor attributes go, the ids get a prefix
+    let dir = tempfile::tempdir()?;
+    std::fs::create_dir(dir.path().join("pattern"))?;
+    std::fs::write(
+        dir.path().join("pattern/test-dots.svg"),
+        r##"<?xml version="1.0"?>
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"
+  xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" width="8" height="8">
+  <!-- a dot -->
+  <defs><circle id="dot" r="1" inkscape:label="Dot"/></defs>
+  <use xlink:href="#dot" x="4" y="4"/>
+</svg>"##,
+    )?;
+
+    let render_in_dir = |style: &str| -> Result<String> {
+        let style_path = dir.path().join("style.toml");
+        std::fs::write(&style_path, style)?;
+        let output = dir.path().join("patterns.svg");
+
+        osm_bpf_to_svg::doit(
+            select_box(),
+            pbf.write("patterns-file").to_string_lossy().to_string(),
+            output.to_string_lossy().to_string(),
+            Some(style_path.to_string_lossy().to_string()),
+        )?;
+
+        Ok(std::fs::read_to_string(output)?)
+    };
+
+    let svg = render_in_dir(
+        r#"
+[[rule]]
+key = "building"
+z-index = 1
+style = "fill:url(#test-dots)"
+"#,
+    )?;
+    assert!(svg.contains(
+        r##"<defs><circle id="pattern-test-dots-dot" r="1"/></defs>
+  <use xlink:href="#pattern-test-dots-dot" x="4" y="4"/>"##
+    ));
+    assert!(svg.contains(r#"<pattern id="test-dots" patternUnits="userSpaceOnUse" width="8""#));
+
+    // only the fill and stroke refer to patterns
+    let err = render_in_dir(
+        r#"
+[[rule]]
+key = "building"
+z-index = 1
+style = "fill:url(#test-stripes)"
+"#,
+    )
+    .unwrap_err();
+    assert!(format!("{:?}", err).contains("line 2: unknown pattern test-stripes"));
+
+    let svg = render_in_dir(
+        r#"
+[[rule]]
+key = "building"
+z-index = 1
+style = "fill:red;filter:url(#shadow)"
+"#,
+    )?;
+    assert!(!svg.contains("<pattern"));
+
+    Ok(())
+}
+
+#[test]
+fn test_grid() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    for projection in ["rd", "webmercator", "utm31"] {
+        let projection: Projection = projection.parse()?;
+        let (x, y) = projection.project(52.0, 5.5);
+        let (lat, lon) = projection.unproject(x, y);
+        assert!((lat - 52.0).abs() < 1e-6 && (lon - 5.5).abs() < 1e-6);
+    }
+
+    let select_box = SelectBox::new(162_000, 444_000, 2_000, 2_000);
+
+    let svg = render_box(&building(false), "grid", select_box, &Options::default())?;
+    // the lines on the border and the one in the middle
+    assert_eq!(svg.matches(r#"class="grid"/>"#).count(), 6);
+    assert_eq!(svg.matches(r#"class="grid-label""#).count(), 4);
+    assert_eq!(svg.matches(">163</text>").count(), 2);
+    assert_eq!(svg.matches(">445</text>").count(), 2);
+    assert!(!svg.contains(r#"class="graticule""#));
+
+    let options = Options {
+        grid: Some(0.0),
+        graticule: Some(0.01),
+        ..Options::default()
+    };
+    let svg = render_box(&building(false), "graticule", select_box, &options)?;
+    assert!(!svg.contains(r#"class="grid"/>"#));
+    assert!(svg.contains(r#"class="graticule"/>"#));
+    assert!(svg.contains("°N</text>") && svg.contains("°E</text>"));
+
+    // a line every metre on 2 km is a mistake
+    let options = Options {
+        grid: Some(1.0),
+        ..Options::default()
+    };
+    let err = render_box(&building(false), "grid-dense", select_box, &options).unwrap_err();
+    assert!(format!("{:?}", err).contains("more than 1000 lines"));
+
+    let style = r#"
+[grid]
+spacing = 500
+style = "stroke:red"
+
+[[rule]]
+key = "building"
+z-index = 1
+style = "fill:red"
+"#;
+    let svg = render_with_style(&building(false), "grid-style", Some(style))?;
+    assert!(svg.contains(".grid { stroke:red }"));
+    assert!(svg.contains(".5</text>"));
+
+    Ok(())
+}
+
+#[test]
+fn test_furniture() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let select_box = SelectBox::new(162_000, 444_000, 2_000, 2_000);
+
+    let svg = render_box(
+        &building(false),
+        "no-furniture",
+        select_box,
+        &Options::default(),
+    )?;
+    assert!(!svg.contains("class=\"scale-bar") && !svg.contains("class=\"north-arrow"));
+
+    let options = Options {
+        furniture: true,
+        title: Some("Veldkaart <Ede>".to_string()),
+        date: Some("2024-05-01".to_string()),
+        ..Options::default()
+    };
+    let svg = render_box(&building(false), "furniture", select_box, &options)?;
+    let furniture = &svg[svg.find(r#"<g id="furniture""#).unwrap()..];
+
+    // a quarter of the 2 km map, at 1 pixel per metre and 96 pixels per inch
+    assert!(furniture.contains(">500 m</text>"));
+    assert!(furniture.contains(">1:3780</text>"));
+    assert!(furniture.contains(">Veldkaart &lt;Ede&gt;</text>"));
+    assert!(furniture.contains(">2024-05-01</text>"));
+    assert!(furniture.contains(">© OpenStreetMap contributors</text>"));
+
+    // true north is west of grid north east of the central meridian of utm 31
+    let angle = |svg: &str| -> f64 {
+        let rotate = &svg[svg.find("rotate(").unwrap() + 7..];
+        rotate.split(' ').next().unwrap().parse().unwrap()
+    };
+    assert!(angle(&svg).abs() < 0.2);
+
+    let (x, y) = Projection::Utm {
+        zone: 31,
+        south: false,
+    }
+    .project(52.0, 5.5);
+    let options = Options {
+        projection: "utm31".parse()?,
+        ..options
+    };
+    let select_box = SelectBox::new(x as i64 - 1000, y as i64 - 1000, 2_000, 2_000);
+    let svg = render_box(&building(false), "furniture-utm", select_box, &options)?;
+    assert!((angle(&svg) + 1.97).abs() < 0.05);
+
+    Ok(())
+}
+
+#[test]
+fn test_print_scale() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    assert_eq!("1:10 000".parse::<Scale>()?, Scale(10000.0));
+    assert!("1:0".parse::<Scale>().is_err());
+    assert!("1:inf".parse::<Scale>().is_err() && "NaN".parse::<Scale>().is_err());
+    assert_eq!(
+        "300x400".parse::<Paper>()?,
+        Paper {
+            width: 300.0,
+            height: 400.0
+        }
+    );
+    assert!("b4".parse::<Paper>().is_err());
+    assert!("infx300".parse::<Paper>().is_err() && "nanx300".parse::<Paper>().is_err());
+
+    let print = Print {
+        scale: "1:10000".parse()?,
+        paper: "A3".parse()?,
+        orientation: "landscape".parse()?,
+        margin: 10.0,
+    };
+
+    // 400 by 277 mm inside the margins, 10 metres a millimetre
+    let select_box = print.select_box(160_000, 442_000)?;
+    assert_eq!((select_box.w(), select_box.h()), (4000, 2770));
+
+    let options = Options {
+        furniture: true,
+        print: Some(print),
+        ..Options::default()
+    };
+    let svg = render_box(&building(false), "print-scale", select_box, &options)?;
+
+    assert!(svg.contains("width=\"420mm\""));
+    assert!(svg.contains("height=\"297mm\""));
+    assert!(svg.contains("viewBox=\"159900 -444870 4200 2970\""));
+    assert!(svg.contains(
+        "<rect x=\"160000\" y=\"-444770\" width=\"4000\" height=\"2770\" class=\"neatline\"/>"
+    ));
+    // the furniture is sized in millimetres on paper by the css
+    assert!(svg.contains(".neatline { fill:none;stroke:black;stroke-width:3 }"));
+    assert!(svg.contains(">1:10000</text>"));
+
+    let too_wide = Print {
+        margin: 150.0,
+        ..print
+    };
+    assert!(too_wide.select_box(0, 0).is_err());
+    let negative = Print {
+        margin: -5.0,
+        ..print
+    };
+    assert!(negative.select_box(0, 0).is_err());
+    let nan = Print {
+        paper: Paper {
+            width: f64::NAN,
+            height: 300.0,
+        },
+        ..print
+    };
+    assert!(nan.select_box(0, 0).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_atlas() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    // sheets of 277 by 190 metres, two by two over the area
+    let options = Options {
+        print: Some(Print {
+            scale: "1:1000".parse()?,
+            paper: Paper::A4,
+            orientation: Orientation::Landscape,
+            margin: 10.0,
+        }),
+        ..Options::default()
+    };
+    let (x, y) = rijksdriehoek::wgs84_to_rijksdriehoek(52.0, 5.5);
+    let area = SelectBox::new(x as i64 - 250, y as i64 - 150, 500, 300);
+    let output = std::env::temp_dir().join("osm-bpf-to-svg-atlas.svg");
+
+    let paths = osm_bpf_to_svg::atlas(
+        area,
+        50,
+        building(false).write("atlas").to_string_lossy().to_string(),
+        output.to_string_lossy().to_string(),
+        &options,
+    )?;
+
+    assert_eq!(paths.len(), 5);
+    assert!(paths[0].ends_with("osm-bpf-to-svg-atlas-1.svg"));
+    assert!(paths[4].ends_with("osm-bpf-to-svg-atlas-index.svg"));
+
+    let sheets: Vec<String> = paths
+        .iter()
+        .map(std::fs::read_to_string)
+        .collect::<Result<_, _>>()?;
+
+    // numbered row by row from the north west, with the neighbours at the
+    // edges
+    assert!(sheets[0].contains(">Sheet 1</text>"));
+    assert!(sheets[0].contains(">2 →</text>") && sheets[0].contains(">↓ 3</text>"));
+    assert!(!sheets[0].contains("↑") && !sheets[0].contains("←"));
+    assert!(sheets[3].contains(">← 3</text>") && sheets[3].contains(">↑ 2</text>"));
+
+    // the building in the middle of the area is on every sheet
+    for sheet in &sheets {
+        assert!(sheet.contains("id=\"10\""));
+    }
+
+    // the index has the frames of all sheets
+    assert_eq!(sheets[4].matches("<rect").count(), 4 + 1);
+    assert_eq!(sheets[4].matches("class=\"sheet-frame\"").count(), 4);
+    assert_eq!(sheets[4].matches("class=\"sheet-frame-number\"").count(), 4);
+
+    Ok(())
+}
+
+#[test]
+fn test_legend() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let pbf = building(false)
+        .node(5, 52.0, 5.502, &[])
+        .node(6, 52.002, 5.502, &[])
+        .way(11, &[5, 6], &[("highway", "residential")])
+        .node(7, 52.0005, 5.503, &[("amenity", "atm")]);
+
+    let svg = render(&pbf, "no-legend")?;
+    assert!(!svg.contains(">Legend</text>"));
+
+    let options = Options {
+        legend: true,
+        ..Options::default()
+    };
+    let svg = render_with_options(&pbf, "legend", &options)?;
+    let legend = &svg[svg.find(">Legend</text>").unwrap()..];
+
+    // a swatch for the area, line and symbol with the caption of the style,
+    // only for the rules on the map
+    assert!(legend.contains("class=\"building-yes\"/>"));
+    assert!(legend.contains(">Building</text>"));
+    assert!(legend.contains("class=\"highway-residential-casing\"/>"));
+    assert!(legend.contains(">Residential road</text>"));
+    assert!(legend.contains("xlink:href=\"#symbol-amenity-atm\""));
+    assert!(legend.contains(">ATM</text>"));
+    assert!(!legend.contains(">Primary road</text>"));
+
+    // without a legend in the rule the tags are the caption, an empty legend
+    // leaves the rule out
+    let style = r#"
+        [[rule]]
+        tags = ["building", "!disused"]
+        z-index = 1
+        style = "fill:red"
+
+        [[rule]]
+        key = "highway"
+        z-index = 1
+        style = "stroke:black;fill:none"
+        legend = ""
+    "#;
+    let path = std::env::temp_dir().join("osm-bpf-to-svg-legend-style.toml");
+    std::fs::write(&path, style)?;
+
+    let options = Options {
+        style_path: Some(path.to_string_lossy().to_string()),
+        ..options
+    };
+    let svg = render_with_options(&pbf, "legend-style", &options)?;
+    let legend = &svg[svg.find(">Legend</text>").unwrap()..];
+
+    // the building is the only caption after the title
+    assert!(legend.contains(">building, !disused</text>"));
+    assert_eq!(legend.matches("class=\"legend-caption\">").count(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_preview() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let output = std::env::temp_dir().join("osm-bpf-to-svg-preview.svg");
+    let preview = || -> Result<String> {
+        osm_bpf_to_svg::preview(output.to_string_lossy().to_string(), &Options::default())?;
+        Ok(std::fs::read_to_string(&output)?)
+    };
+
+    let svg = preview()?;
+
+    // a cell for every rule of the built in style
+    let rules = include_str!("../style/default.toml")
+        .lines()
+        .filter(|&line| line == "[[rule]]")
+        .count();
+    assert_eq!(svg.matches("<g class=\"preview-").count(), rules);
+
+    let cell = &svg[svg
+        .find("<g class=\"preview-highway-residential\">")
+        .unwrap()..];
+    let cell = &cell[..cell.find("</g>").unwrap()];
+    assert!(cell.contains("class=\"highway-residential-casing\"/>"));
+    assert!(cell.contains("class=\"preview-caption\">Residential road</text>"));
+    assert!(cell.contains("class=\"preview-tags\">highway=residential</text>"));
+    assert!(svg.contains(".preview-tags { font-family:monospace;"));
+    assert!(!svg.contains("font-family=\""));
+
+    // the symbols and patterns of all rules
+    assert!(svg.contains("<symbol id=\"symbol-amenity-atm\""));
+    assert!(svg.contains("<pattern id=\"parking\""));
+
+    // the same style gives the same svg, to compare versions of a style
+    assert_eq!(preview()?, svg);
+
+    Ok(())
+}
+
+#[test]
+fn test_pdf() -> Result<()> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    // the pdf needs a sans-serif font of the system for its text
+    let mut fonts = svg2pdf::usvg::fontdb::Database::new();
+    fonts.load_system_fonts();
+    if !fonts.faces().any(|face| {
+        face.families
+            .iter()
+            .any(|(name, _)| name == "Arial" || name == "DejaVu Sans")
+    }) {
+        eprintln!("test_pdf skipped: no Arial or DejaVu Sans font on this system");
+        return Ok(());
+    }
+
+    let pbf = building(false)
+        .node(5, 52.0, 5.502, &[])
+        .node(6, 52.0, 5.503, &[])
+        .node(7, 52.001, 5.503, &[])
+        .way(11, &[5, 6, 7, 5], &[("amenity", "parking")]);
+
+    let print = Print {
+        scale: "1:5000".parse()?,
+        paper: "a3".parse()?,
+        orientation: Orientation::Landscape,
+        margin: 10.0,
+    };
+    let (x, y) = rijksdriehoek::wgs84_to_rijksdriehoek(52.0, 5.5);
+    let options = Options {
+        print: Some(print),
+        furniture: true,
+        title: Some("Ede".to_string()),
+        graticule: Some(0.005),
+        ..Options::default()
+    };
+
+    let write_pdf = |name: &str, options: &Options| -> Result<Vec<u8>> {
+        let output = std::env::temp_dir().join(format!("osm-bpf-to-svg-{}.pdf", name));
+        osm_bpf_to_svg::doit_with_options(
+            print.select_box(x as i64 - 500, y as i64 - 500)?,
+            pbf.write(name).to_string_lossy().to_string(),
+            output.to_string_lossy().to_string(),
+            options,
+        )?;
+
+        Ok(std::fs::read(&output)?)
+    };
+
+    // the drawing operators of the compressed content streams
+    let content = |pdf: &[u8]| -> String {
+        let mut content = String::new();
+        let mut rest = pdf;
+
+        while let Some(start) = find(rest, b"stream\n") {
+            rest = &rest[start + 7..];
+            let Some(end) = find(rest, b"endstream") else {
+                break;
+            };
+            if let Ok(data) = miniz_oxide::inflate::decompress_to_vec_zlib(&rest[..end]) {
+                content += &String::from_utf8_lossy(&data);
+            }
+            rest = &rest[end + 9..];
+        }
+
+        content
+    };
+
+    let pdf = write_pdf("pdf", &options)?;
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.starts_with("%PDF-"));
+
+    // a page of 420 by 297 mm in points
+    let media_box = &text[text.find("/MediaBox [").unwrap() + 11..];
+    let size: Vec<f64> = media_box[..media_box.find(']').unwrap()]
+        .split_whitespace()
+        .map(|v| v.parse().unwrap())
+        .collect();
+    assert_eq!(size.len(), 4);
+    assert!((size[2] - 420.0 / 25.4 * 72.0).abs() < 0.1);
+    assert!((size[3] - 297.0 / 25.4 * 72.0).abs() < 0.1);
+
+    // the parking pattern is a pdf pattern, not an svg reference
+    assert!(text.contains("/PatternType 1"));
+
+    // the dashes of the graticule are a dash pattern
+    let with_title = content(&pdf);
+    assert!(with_title.contains("[8 4] 0 d"), "no dashes");
+
+    // the title is drawn as glyph outlines, it adds curves to the page
+    let untitled = write_pdf(
+        "pdf-untitled",
+        &Options {
+            title: None,
+            ..options.clone()
+        },
+    )?;
+    let curves = |content: &str| content.lines().filter(|l| l.ends_with(" c")).count();
+    assert!(curves(&with_title) > curves(&content(&untitled)), "no text");
+
+    Ok(())
+}